@@ -0,0 +1,367 @@
+/* RTSP player C API. Generated by cbindgen from crates/player-sys, do not edit. */
+
+#ifndef RTSP_PLAYER_H
+#define RTSP_PLAYER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#define RTSP_ERROR_OK 0
+
+#define RTSP_ERROR_INIT 1
+
+#define RTSP_ERROR_INVALID_CONFIG 2
+
+#define RTSP_ERROR_AUTHENTICATION 3
+
+#define RTSP_ERROR_CONNECTION 4
+
+#define RTSP_ERROR_TIMEOUT 5
+
+#define RTSP_ERROR_UNSUPPORTED_CODEC 6
+
+#define RTSP_ERROR_MISSING_PLUGIN 7
+
+#define RTSP_ERROR_STATE_CHANGE 8
+
+#define RTSP_ERROR_SINK 9
+
+#define RTSP_ERROR_STREAM 10
+
+#define RTSP_ERROR_WINDOWS 11
+
+#define RTSP_ERROR_RECORDING 12
+
+#define RTSP_ERROR_SNAPSHOT 13
+
+#define RTSP_ERROR_LIMIT_REACHED 14
+
+#define RTSP_ERROR_INVALID_ARGUMENT 15
+
+#define RTSP_ERROR_PANIC 16
+
+#define RTSP_EVENT_STATE_CHANGED 0
+
+#define RTSP_EVENT_STREAM_STARTED 1
+
+#define RTSP_EVENT_BUFFERING 2
+
+#define RTSP_EVENT_END_OF_STREAM 3
+
+#define RTSP_EVENT_ERROR 4
+
+#define RTSP_EVENT_AUTHENTICATION_FAILED 5
+
+#define RTSP_EVENT_CERTIFICATE_ERROR 6
+
+#define RTSP_EVENT_RECONNECTING 7
+
+#define RTSP_EVENT_RECONNECTED 8
+
+#define RTSP_EVENT_TRANSPORT_FALLBACK 9
+
+#define RTSP_EVENT_STALLED 10
+
+#define RTSP_EVENT_RECONNECT_GAVE_UP 11
+
+#define RTSP_EVENT_RECORDING_STARTED 12
+
+#define RTSP_EVENT_RECORDING_STOPPED 13
+
+#define RTSP_EVENT_SEGMENT_CLOSED 14
+
+#define RTSP_EVENT_VIDEO_INFO 15
+
+#define RTSP_STATE_STOPPED 0
+
+#define RTSP_STATE_READY 1
+
+#define RTSP_STATE_PAUSED 2
+
+#define RTSP_STATE_PLAYING 3
+
+#define RTSP_TRANSPORT_AUTO 0
+
+#define RTSP_TRANSPORT_UDP 1
+
+#define RTSP_TRANSPORT_UDP_MULTICAST 2
+
+#define RTSP_TRANSPORT_TCP 3
+
+#define RTSP_TRANSPORT_HTTP 4
+
+#define RTSP_PIXEL_FORMAT_RGB 0
+
+#define RTSP_PIXEL_FORMAT_RGBA 1
+
+#define RTSP_PIXEL_FORMAT_BGR 2
+
+#define RTSP_PIXEL_FORMAT_BGRA 3
+
+#define RTSP_PIXEL_FORMAT_GRAY8 4
+
+// Opaque player handle.
+typedef struct RtspPlayerHandle RtspPlayerHandle;
+
+// Settings for `rtsp_player_create_with_options`. Initialize with
+// `rtsp_player_options_init` so fields added later get their defaults.
+typedef struct RtspPlayerOptions {
+  uint32_t latency_ms;
+  // One of the `RTSP_TRANSPORT_*` constants.
+  uint32_t transport;
+  // Decode without opening a window.
+  bool headless;
+  bool audio;
+  // NULL for streams without authentication.
+  const char *username;
+  const char *password;
+  // Name used in logs instead of the URL, may be NULL.
+  const char *name;
+  // Decode into memory for `rtsp_player_set_frame_callback` instead of a
+  // window. Implies `headless` and no audio.
+  bool frames;
+  // `RTSP_PIXEL_FORMAT_*` of the frames passed to the frame callback.
+  uint32_t frame_format;
+} RtspPlayerOptions;
+
+// A decoded frame copied out of the player, released with
+// `rtsp_player_snapshot_free`. Rows are tightly packed.
+typedef struct RtspSnapshot {
+  uint8_t *data;
+  size_t len;
+  uint32_t width;
+  uint32_t height;
+  uint32_t stride;
+  // One of the `RTSP_PIXEL_FORMAT_*` constants.
+  uint32_t format;
+} RtspSnapshot;
+
+// Playback statistics, see `rtsp_player_get_stats`.
+typedef struct RtspStats {
+  // -1 when unknown.
+  int64_t position_ms;
+  // -1 for live streams.
+  int64_t duration_ms;
+  // Attempts since the stream last reached playing.
+  uint32_t reconnect_attempts;
+  // Attempts since the player was created.
+  uint64_t reconnects_total;
+  uint64_t stalls;
+  uint64_t uptime_ms;
+  uint64_t bitrate_bps;
+  double fps;
+  uint64_t frames_decoded;
+  uint64_t frames_rendered;
+  uint64_t frames_dropped;
+  uint64_t qos_dropped;
+  uint64_t jitterbuffer_lost;
+  uint64_t jitterbuffer_late;
+  uint64_t packets_received;
+  // Negative when duplicates outnumber losses.
+  int64_t packets_lost;
+  double jitter_ms;
+} RtspStats;
+
+// A player event. Only the fields listed for its `kind` are meaningful, the
+// rest are zero. The struct and `message` are only valid during the callback.
+typedef struct RtspEvent {
+  // One of the `RTSP_EVENT_*` constants.
+  uint32_t kind;
+  // `STATE_CHANGED`: the new and previous `RTSP_STATE_*`.
+  uint32_t state;
+  uint32_t old_state;
+  // `BUFFERING`: 0 to 100.
+  int32_t buffering_percent;
+  // `ERROR`: an `RTSP_ERROR_*` code.
+  int32_t error_code;
+  // `RECONNECTING`: the attempt number. `RECONNECTED`, `RECONNECT_GAVE_UP`:
+  // the attempts made.
+  uint32_t attempt;
+  // `RECONNECTING`: delay before the attempt. `STALLED`: time without
+  // frames. `TRANSPORT_FALLBACK`: time waited for UDP data.
+  uint64_t duration_ms;
+  // `VIDEO_INFO`: picture size and frame rate.
+  uint32_t width;
+  uint32_t height;
+  double framerate;
+  // Error message, username, certificate problems, reconnect reason,
+  // transport change, file path or codec, depending on `kind`. May be NULL.
+  const char *message;
+} RtspEvent;
+
+// Called on the player's event thread, never concurrently for one player.
+typedef void (*RtspEventCallback)(const struct RtspEvent *event, void *user_data);
+
+// A decoded frame lent to the frame callback without copying. `data` points
+// into the decoder's buffer and is only valid until the callback returns.
+typedef struct RtspFrame {
+  // First row of pixels, `stride * height` bytes.
+  const uint8_t *data;
+  size_t len;
+  uint32_t width;
+  uint32_t height;
+  // Bytes between the starts of two rows, may include padding.
+  uint32_t stride;
+  // One of the `RTSP_PIXEL_FORMAT_*` constants.
+  uint32_t format;
+  // Presentation timestamp in microseconds of stream running time, -1 when unknown.
+  int64_t pts_us;
+  // Capture time in microseconds since the Unix epoch, from the camera's
+  // RTCP sender reports or the decode time if it sends none.
+  int64_t captured_at_us;
+  // Counts the frames the player delivered, so gaps show frames skipped for
+  // this callback. Frames dropped before delivery are not counted.
+  uint64_t frame_number;
+} RtspFrame;
+
+// Called on the player's frame thread, never concurrently for one player.
+typedef void (*RtspFrameCallback)(const struct RtspFrame *frame, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Fill `options` with the defaults `rtsp_player_create` uses.
+//
+// # Safety
+// `options` must be NULL or point to writable memory for an `RtspPlayerOptions`.
+void rtsp_player_options_init(struct RtspPlayerOptions *options);
+
+// Create a stopped player for `url`. Returns NULL on failure.
+//
+// # Safety
+// `url` must be NULL or a NUL-terminated string.
+RtspPlayerHandle *rtsp_player_create(const char *url);
+
+// Create a stopped player for `url` with `options`. Returns NULL on failure.
+//
+// # Safety
+// `url` must be a NUL-terminated string and `options` NULL or a valid
+// `RtspPlayerOptions` whose strings are NULL or NUL-terminated.
+RtspPlayerHandle *rtsp_player_create_with_options(const char *url,
+                                                  const struct RtspPlayerOptions *options);
+
+// Stop the player and release the handle. NULL is ignored.
+//
+// # Safety
+// `handle` must come from `rtsp_player_create*` and not be used afterwards.
+void rtsp_player_destroy(RtspPlayerHandle *handle);
+
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_play(RtspPlayerHandle *handle);
+
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_pause(RtspPlayerHandle *handle);
+
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_stop(RtspPlayerHandle *handle);
+
+// Seek to `position`, a fraction of the duration between 0.0 and 1.0.
+// Live streams without a duration ignore it.
+//
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_seek(RtspPlayerHandle *handle, double position);
+
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_is_playing(RtspPlayerHandle *handle);
+
+// Render into a native window (HWND on Windows, X11 window id elsewhere).
+//
+// # Safety
+// `handle` must be NULL or a live handle, `hwnd` a window that outlives the player.
+bool rtsp_player_set_hwnd(RtspPlayerHandle *handle, void *hwnd);
+
+// Copy the last rendered frame into `snapshot` in the given
+// `RTSP_PIXEL_FORMAT_*`. Release it with `rtsp_player_snapshot_free`.
+//
+// # Safety
+// `handle` must be NULL or a live handle, `snapshot` NULL or writable.
+bool rtsp_player_snapshot(RtspPlayerHandle *handle, uint32_t format, struct RtspSnapshot *snapshot);
+
+// Release the pixel data of a snapshot and reset it. Safe to call twice.
+//
+// # Safety
+// `snapshot` must be NULL or filled by `rtsp_player_snapshot`.
+void rtsp_player_snapshot_free(struct RtspSnapshot *snapshot);
+
+// Save the last rendered frame as JPEG (`.jpg`, `.jpeg`) or PNG.
+//
+// # Safety
+// `handle` must be NULL or a live handle, `path` NULL or a NUL-terminated UTF-8 string.
+bool rtsp_player_snapshot_to_file(RtspPlayerHandle *handle, const char *path);
+
+// Record to `path` without re-encoding, the container follows the extension.
+//
+// # Safety
+// `handle` must be NULL or a live handle, `path` NULL or a NUL-terminated UTF-8 string.
+bool rtsp_player_start_recording(RtspPlayerHandle *handle, const char *path);
+
+// # Safety
+// `handle` must be NULL or a live handle.
+bool rtsp_player_stop_recording(RtspPlayerHandle *handle);
+
+// Copy the latest statistics into `stats`. They are refreshed once per
+// stats interval, one second by default.
+//
+// # Safety
+// `handle` must be NULL or a live handle, `stats` NULL or writable.
+bool rtsp_player_get_stats(RtspPlayerHandle *handle, struct RtspStats *stats);
+
+// Call `callback` with `user_data` for every player event, replacing the
+// previous callback. NULL removes it.
+//
+// Events are delivered in order on a thread owned by the player, one at a
+// time. Once this function or `rtsp_player_destroy` returns, the previous
+// callback is not running and will not be called again, so its `user_data`
+// may be freed. Called from inside the callback, neither waits; the change
+// applies from the next event.
+//
+// # Safety
+// `handle` must be NULL or a live handle. `user_data` must stay valid until
+// the callback is replaced or the player destroyed.
+bool rtsp_player_set_event_callback(RtspPlayerHandle *handle,
+                                    RtspEventCallback callback,
+                                    void *user_data);
+
+// Call `callback` with `user_data` for every decoded frame, replacing the
+// previous callback. NULL removes it. The player must be created with the
+// `frames` option.
+//
+// The frame and its pixel data are lent without copying and only valid
+// until the callback returns; copy what you need to keep. Frames arrive on a
+// thread owned by the player, one at a time. A slow callback makes the
+// player skip frames for it rather than stall the stream. Replacing the
+// callback waits for a running call like `rtsp_player_set_event_callback`.
+//
+// # Safety
+// `handle` must be NULL or a live handle. `user_data` must stay valid until
+// the callback is replaced or the player destroyed.
+bool rtsp_player_set_frame_callback(RtspPlayerHandle *handle,
+                                    RtspFrameCallback callback,
+                                    void *user_data);
+
+// `RTSP_ERROR_*` code of the last failed call on this thread,
+// `RTSP_ERROR_OK` if the last call succeeded.
+int32_t rtsp_player_get_last_error_code(void);
+
+// Message of the last failed call on this thread, NULL if the last call
+// succeeded. Release it with `rtsp_player_free_string`.
+char *rtsp_player_get_last_error(void);
+
+// Release a string returned by this library. NULL is ignored.
+//
+// # Safety
+// `text` must be NULL or come from `rtsp_player_get_last_error`, and be freed once.
+void rtsp_player_free_string(char *text);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* RTSP_PLAYER_H */
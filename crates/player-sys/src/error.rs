@@ -0,0 +1,81 @@
+use player::{ErrorCode, PlayerError};
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CString;
+
+/// Why an FFI call failed, kept per thread for `rtsp_player_get_last_error*`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FfiError {
+    pub(crate) code: ErrorCode,
+    pub(crate) message: String,
+}
+
+impl FfiError {
+    pub(crate) fn invalid_argument(message: &str) -> Self {
+        FfiError { code: ErrorCode::InvalidArgument, message: message.to_string() }
+    }
+
+    pub(crate) fn panic(payload: &(dyn Any + Send)) -> Self {
+        let detail = payload
+            .downcast_ref::<&str>()
+            .map(|text| text.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        FfiError { code: ErrorCode::Panic, message: format!("Internal error: {}", detail) }
+    }
+}
+
+impl From<PlayerError> for FfiError {
+    fn from(err: PlayerError) -> Self {
+        FfiError { code: err.code(), message: err.to_string() }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<FfiError>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn set_last_error(error: FfiError) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(error));
+}
+
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+pub(crate) fn last_error_code() -> ErrorCode {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ErrorCode::Ok, |error| error.code))
+}
+
+/// The message as a C string; interior NUL bytes are dropped.
+pub(crate) fn last_error_message() -> Option<CString> {
+    LAST_ERROR.with(|last| {
+        let last = last.borrow();
+        let message = last.as_ref()?.message.replace('\0', "");
+        CString::new(message).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_error_is_per_thread() {
+        set_last_error(FfiError::invalid_argument("Player handle is NULL"));
+        assert_eq!(last_error_code(), ErrorCode::InvalidArgument);
+
+        std::thread::spawn(|| assert_eq!(last_error_code(), ErrorCode::Ok)).join().unwrap();
+        assert_eq!(last_error_message().unwrap().to_str().unwrap(), "Player handle is NULL");
+
+        clear_last_error();
+        assert!(last_error_message().is_none());
+    }
+
+    #[test]
+    fn test_player_errors_keep_their_code() {
+        let error = FfiError::from(PlayerError::ConfigError("Latency must not exceed 60 seconds".into()));
+        assert_eq!(error.code, ErrorCode::InvalidConfig);
+        assert!(error.message.contains("Latency"));
+    }
+}
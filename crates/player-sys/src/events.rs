@@ -0,0 +1,309 @@
+use player::{PlayerEvent, PlayerState, RtspPlayer};
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{JoinHandle, ThreadId};
+use std::time::Duration;
+
+pub const RTSP_EVENT_STATE_CHANGED: u32 = 0;
+pub const RTSP_EVENT_STREAM_STARTED: u32 = 1;
+pub const RTSP_EVENT_BUFFERING: u32 = 2;
+pub const RTSP_EVENT_END_OF_STREAM: u32 = 3;
+pub const RTSP_EVENT_ERROR: u32 = 4;
+pub const RTSP_EVENT_AUTHENTICATION_FAILED: u32 = 5;
+pub const RTSP_EVENT_CERTIFICATE_ERROR: u32 = 6;
+pub const RTSP_EVENT_RECONNECTING: u32 = 7;
+pub const RTSP_EVENT_RECONNECTED: u32 = 8;
+pub const RTSP_EVENT_TRANSPORT_FALLBACK: u32 = 9;
+pub const RTSP_EVENT_STALLED: u32 = 10;
+pub const RTSP_EVENT_RECONNECT_GAVE_UP: u32 = 11;
+pub const RTSP_EVENT_RECORDING_STARTED: u32 = 12;
+pub const RTSP_EVENT_RECORDING_STOPPED: u32 = 13;
+pub const RTSP_EVENT_SEGMENT_CLOSED: u32 = 14;
+pub const RTSP_EVENT_VIDEO_INFO: u32 = 15;
+
+pub const RTSP_STATE_STOPPED: u32 = 0;
+pub const RTSP_STATE_READY: u32 = 1;
+pub const RTSP_STATE_PAUSED: u32 = 2;
+pub const RTSP_STATE_PLAYING: u32 = 3;
+
+/// How long the delivery thread waits for an event before checking whether
+/// it should exit.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A player event. Only the fields listed for its `kind` are meaningful, the
+/// rest are zero. The struct and `message` are only valid during the callback.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RtspEvent {
+    /// One of the `RTSP_EVENT_*` constants.
+    pub kind: u32,
+    /// `STATE_CHANGED`: the new and previous `RTSP_STATE_*`.
+    pub state: u32,
+    pub old_state: u32,
+    /// `BUFFERING`: 0 to 100.
+    pub buffering_percent: i32,
+    /// `ERROR`: an `RTSP_ERROR_*` code.
+    pub error_code: i32,
+    /// `RECONNECTING`: the attempt number. `RECONNECTED`, `RECONNECT_GAVE_UP`:
+    /// the attempts made.
+    pub attempt: u32,
+    /// `RECONNECTING`: delay before the attempt. `STALLED`: time without
+    /// frames. `TRANSPORT_FALLBACK`: time waited for UDP data.
+    pub duration_ms: u64,
+    /// `VIDEO_INFO`: picture size and frame rate.
+    pub width: u32,
+    pub height: u32,
+    pub framerate: f64,
+    /// Error message, username, certificate problems, reconnect reason,
+    /// transport change, file path or codec, depending on `kind`. May be NULL.
+    pub message: *const c_char,
+}
+
+impl Default for RtspEvent {
+    fn default() -> Self {
+        RtspEvent {
+            kind: 0,
+            state: 0,
+            old_state: 0,
+            buffering_percent: 0,
+            error_code: 0,
+            attempt: 0,
+            duration_ms: 0,
+            width: 0,
+            height: 0,
+            framerate: 0.0,
+            message: ptr::null(),
+        }
+    }
+}
+
+/// Called on the player's event thread, never concurrently for one player.
+pub type RtspEventCallback = Option<unsafe extern "C" fn(event: *const RtspEvent, user_data: *mut c_void)>;
+
+fn state_code(state: PlayerState) -> u32 {
+    match state {
+        PlayerState::Stopped => RTSP_STATE_STOPPED,
+        PlayerState::Ready => RTSP_STATE_READY,
+        PlayerState::Paused => RTSP_STATE_PAUSED,
+        PlayerState::Playing => RTSP_STATE_PLAYING,
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// The C form of `event` and the text its `message` should point to.
+/// Audio info and stats are not forwarded, stats are polled instead.
+pub(crate) fn to_c_event(event: &PlayerEvent) -> Option<(RtspEvent, Option<String>)> {
+    let (event, message) = match event {
+        PlayerEvent::StateChanged { old, current } => (
+            RtspEvent { kind: RTSP_EVENT_STATE_CHANGED, state: state_code(*current), old_state: state_code(*old), ..Default::default() },
+            None,
+        ),
+        PlayerEvent::StreamStarted => (RtspEvent { kind: RTSP_EVENT_STREAM_STARTED, ..Default::default() }, None),
+        PlayerEvent::Buffering(percent) => (
+            RtspEvent { kind: RTSP_EVENT_BUFFERING, buffering_percent: *percent, ..Default::default() },
+            None,
+        ),
+        PlayerEvent::EndOfStream => (RtspEvent { kind: RTSP_EVENT_END_OF_STREAM, ..Default::default() }, None),
+        PlayerEvent::Error(error) => (
+            RtspEvent { kind: RTSP_EVENT_ERROR, error_code: error.code() as i32, ..Default::default() },
+            Some(error.to_string()),
+        ),
+        PlayerEvent::AuthenticationFailed { username } => (
+            RtspEvent { kind: RTSP_EVENT_AUTHENTICATION_FAILED, ..Default::default() },
+            username.clone(),
+        ),
+        PlayerEvent::CertificateError { problems, accepted } => (
+            RtspEvent { kind: RTSP_EVENT_CERTIFICATE_ERROR, ..Default::default() },
+            Some(format!("{:?} ({})", problems, if *accepted { "accepted" } else { "rejected" })),
+        ),
+        PlayerEvent::Reconnecting { attempt, delay, reason, .. } => (
+            RtspEvent { kind: RTSP_EVENT_RECONNECTING, attempt: *attempt, duration_ms: millis(*delay), ..Default::default() },
+            Some(format!("{:?}", reason)),
+        ),
+        PlayerEvent::Reconnected { attempts } => (
+            RtspEvent { kind: RTSP_EVENT_RECONNECTED, attempt: *attempts, ..Default::default() },
+            None,
+        ),
+        PlayerEvent::TransportFallback { from, to, waited } => (
+            RtspEvent { kind: RTSP_EVENT_TRANSPORT_FALLBACK, duration_ms: millis(*waited), ..Default::default() },
+            Some(format!("{} -> {}", from, to)),
+        ),
+        PlayerEvent::Stalled { idle } => (
+            RtspEvent { kind: RTSP_EVENT_STALLED, duration_ms: millis(*idle), ..Default::default() },
+            None,
+        ),
+        PlayerEvent::ReconnectGaveUp { attempts } => (
+            RtspEvent { kind: RTSP_EVENT_RECONNECT_GAVE_UP, attempt: *attempts, ..Default::default() },
+            None,
+        ),
+        PlayerEvent::RecordingStarted { path } => (
+            RtspEvent { kind: RTSP_EVENT_RECORDING_STARTED, ..Default::default() },
+            Some(path.display().to_string()),
+        ),
+        PlayerEvent::RecordingStopped { path } => (
+            RtspEvent { kind: RTSP_EVENT_RECORDING_STOPPED, ..Default::default() },
+            Some(path.display().to_string()),
+        ),
+        PlayerEvent::SegmentClosed { path } => (
+            RtspEvent { kind: RTSP_EVENT_SEGMENT_CLOSED, ..Default::default() },
+            Some(path.display().to_string()),
+        ),
+        PlayerEvent::VideoInfo(info) => (
+            RtspEvent {
+                kind: RTSP_EVENT_VIDEO_INFO,
+                width: info.width,
+                height: info.height,
+                framerate: info.framerate,
+                ..Default::default()
+            },
+            Some(info.codec.clone()),
+        ),
+        PlayerEvent::AudioInfo(_) | PlayerEvent::Stats(_) => return None,
+    };
+    Some((event, message))
+}
+
+#[derive(Clone, Copy)]
+struct Registration {
+    callback: unsafe extern "C" fn(*const RtspEvent, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// The host promises that `user_data` may be used from the event thread
+unsafe impl Send for Registration {}
+
+#[derive(Default)]
+struct Slot {
+    registration: Option<Registration>,
+    delivering: bool,
+}
+
+#[derive(Default)]
+struct DeliveryState {
+    slot: Mutex<Slot>,
+    delivered: Condvar,
+    stop: AtomicBool,
+}
+
+/// The thread forwarding one player's events to its C callback.
+///
+/// Replacing or removing the callback waits until a running call has
+/// returned, so `user_data` can be freed right afterwards. Called from inside
+/// the callback it cannot wait and takes effect from the next event.
+pub(crate) struct EventDelivery {
+    shared: Arc<DeliveryState>,
+    thread: Option<JoinHandle<()>>,
+    thread_id: ThreadId,
+}
+
+impl EventDelivery {
+    pub(crate) fn spawn(player: &RtspPlayer) -> std::io::Result<Self> {
+        let receiver = player.subscribe();
+        let shared = Arc::new(DeliveryState::default());
+        let thread_shared = Arc::clone(&shared);
+        let thread = std::thread::Builder::new()
+            .name("rtsp-player-ffi-events".into())
+            .spawn(move || {
+                while !thread_shared.stop.load(Ordering::Acquire) {
+                    if let Some(event) = receiver.recv_timeout(POLL_INTERVAL) {
+                        thread_shared.deliver(&event);
+                    }
+                }
+            })?;
+        let thread_id = thread.thread().id();
+        Ok(EventDelivery { shared, thread: Some(thread), thread_id })
+    }
+
+    pub(crate) fn set_callback(&self, callback: RtspEventCallback, user_data: *mut c_void) {
+        let mut slot = self.shared.slot.lock().unwrap();
+        slot.registration = callback.map(|callback| Registration { callback, user_data });
+        if std::thread::current().id() != self.thread_id {
+            while slot.delivering {
+                slot = self.shared.delivered.wait(slot).unwrap();
+            }
+        }
+    }
+
+    /// Remove the callback and stop the thread.
+    pub(crate) fn shutdown(&mut self) {
+        self.set_callback(None, ptr::null_mut());
+        self.shared.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take()
+            && thread.thread().id() != std::thread::current().id()
+        {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for EventDelivery {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl DeliveryState {
+    fn deliver(&self, event: &PlayerEvent) {
+        let Some((mut c_event, message)) = to_c_event(event) else { return };
+        let registration = {
+            let mut slot = self.slot.lock().unwrap();
+            let Some(registration) = slot.registration else { return };
+            slot.delivering = true;
+            registration
+        };
+
+        let message = message.and_then(|text| CString::new(text.replace('\0', "")).ok());
+        c_event.message = message.as_ref().map_or(ptr::null(), |text| text.as_ptr());
+        unsafe { (registration.callback)(&c_event, registration.user_data) };
+
+        self.slot.lock().unwrap().delivering = false;
+        self.delivered.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use player::PlayerError;
+
+    #[test]
+    fn test_events_map_to_c() {
+        let (event, message) = to_c_event(&PlayerEvent::StateChanged {
+            old: PlayerState::Paused,
+            current: PlayerState::Playing,
+        })
+        .unwrap();
+        assert_eq!((event.kind, event.state, event.old_state), (RTSP_EVENT_STATE_CHANGED, RTSP_STATE_PLAYING, RTSP_STATE_PAUSED));
+        assert_eq!(message, None);
+
+        let (event, message) = to_c_event(&PlayerEvent::Error(PlayerError::ConfigError("bad".into()))).unwrap();
+        assert_eq!((event.kind, event.error_code), (RTSP_EVENT_ERROR, 2));
+        assert!(message.unwrap().contains("bad"));
+
+        assert!(to_c_event(&PlayerEvent::Stats(Default::default())).is_none());
+    }
+
+    unsafe extern "C" fn count(event: *const RtspEvent, user_data: *mut c_void) {
+        let (event, seen) = unsafe { (&*event, &*(user_data as *const Mutex<Vec<u32>>)) };
+        seen.lock().unwrap().push(event.kind);
+    }
+
+    #[test]
+    fn test_unregistered_callback_is_not_called() {
+        let shared = DeliveryState::default();
+        let seen = Mutex::new(Vec::new());
+        let user_data = &seen as *const Mutex<Vec<u32>> as *mut c_void;
+        shared.slot.lock().unwrap().registration = Some(Registration { callback: count, user_data });
+
+        shared.deliver(&PlayerEvent::StreamStarted);
+        shared.slot.lock().unwrap().registration = None;
+        shared.deliver(&PlayerEvent::EndOfStream);
+        assert_eq!(*seen.lock().unwrap(), vec![RTSP_EVENT_STREAM_STARTED]);
+        assert!(!shared.slot.lock().unwrap().delivering);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::pixel_format_code;
+use player::{Frame, PlayerError, RtspPlayer, SubscriptionId};
+use std::ffi::c_void;
+use std::ptr;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// A decoded frame lent to the frame callback without copying. `data` points
+/// into the decoder's buffer and is only valid until the callback returns.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RtspFrame {
+    /// First row of pixels, `stride * height` bytes.
+    pub data: *const u8,
+    pub len: usize,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes between the starts of two rows, may include padding.
+    pub stride: u32,
+    /// One of the `RTSP_PIXEL_FORMAT_*` constants.
+    pub format: u32,
+    /// Presentation timestamp in microseconds of stream running time, -1 when unknown.
+    pub pts_us: i64,
+    /// Capture time in microseconds since the Unix epoch, from the camera's
+    /// RTCP sender reports or the decode time if it sends none.
+    pub captured_at_us: i64,
+    /// Counts the frames the player delivered, so gaps show frames skipped for
+    /// this callback. Frames dropped before delivery are not counted.
+    pub frame_number: u64,
+}
+
+/// Called on the player's frame thread, never concurrently for one player.
+pub type RtspFrameCallback = Option<unsafe extern "C" fn(frame: *const RtspFrame, user_data: *mut c_void)>;
+
+fn micros(duration: Duration) -> i64 {
+    i64::try_from(duration.as_micros()).unwrap_or(i64::MAX)
+}
+
+pub(crate) fn to_c_frame(frame: &Frame) -> RtspFrame {
+    let data = frame.data();
+    RtspFrame {
+        data: data.as_ptr(),
+        len: data.len(),
+        width: frame.width,
+        height: frame.height,
+        stride: frame.stride as u32,
+        format: pixel_format_code(frame.format),
+        pts_us: frame.pts.map_or(-1, micros),
+        captured_at_us: frame.captured_at.duration_since(UNIX_EPOCH).map_or(0, micros),
+        frame_number: frame.frame_number,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Registration {
+    callback: unsafe extern "C" fn(*const RtspFrame, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// The host promises that `user_data` may be used from the frame thread
+unsafe impl Send for Registration {}
+
+#[derive(Default)]
+struct Slot {
+    registration: Option<Registration>,
+    /// The thread inside the callback, if any.
+    delivering: Option<ThreadId>,
+}
+
+#[derive(Default)]
+struct FrameSlot {
+    slot: Mutex<Slot>,
+    delivered: Condvar,
+}
+
+impl FrameSlot {
+    fn deliver(&self, frame: &RtspFrame) {
+        let registration = {
+            let mut slot = self.slot.lock().unwrap();
+            let Some(registration) = slot.registration else { return };
+            slot.delivering = Some(std::thread::current().id());
+            registration
+        };
+
+        unsafe { (registration.callback)(frame, registration.user_data) };
+
+        self.slot.lock().unwrap().delivering = None;
+        self.delivered.notify_all();
+    }
+}
+
+/// Forwards one player's decoded frames to its C callback, on the thread
+/// `RtspPlayer::on_frame` runs callbacks on.
+///
+/// Like `EventDelivery`, replacing or removing the callback waits for a
+/// running call to return, except from inside the callback itself.
+pub(crate) struct FrameDelivery {
+    shared: Arc<FrameSlot>,
+    subscription: SubscriptionId,
+}
+
+impl FrameDelivery {
+    pub(crate) fn subscribe(player: &RtspPlayer) -> std::result::Result<Self, PlayerError> {
+        let shared = Arc::new(FrameSlot::default());
+        let thread_shared = Arc::clone(&shared);
+        let subscription = player.on_frame(move |frame| thread_shared.deliver(&to_c_frame(frame)))?;
+        Ok(FrameDelivery { shared, subscription })
+    }
+
+    pub(crate) fn set_callback(&self, callback: RtspFrameCallback, user_data: *mut c_void) {
+        let mut slot = self.shared.slot.lock().unwrap();
+        slot.registration = callback.map(|callback| Registration { callback, user_data });
+        let current = std::thread::current().id();
+        while slot.delivering.is_some_and(|thread| thread != current) {
+            slot = self.shared.delivered.wait(slot).unwrap();
+        }
+    }
+
+    /// Remove the callback and end the player's frame thread.
+    pub(crate) fn unsubscribe(&self, player: &RtspPlayer) {
+        self.set_callback(None, ptr::null_mut());
+        player.remove_frame_callback(self.subscription);
+    }
+}
+
+impl Drop for FrameDelivery {
+    fn drop(&mut self) {
+        self.set_callback(None, ptr::null_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(frame_number: u64) -> RtspFrame {
+        RtspFrame {
+            data: ptr::null(),
+            len: 0,
+            width: 2,
+            height: 2,
+            stride: 8,
+            format: crate::RTSP_PIXEL_FORMAT_RGBA,
+            pts_us: -1,
+            captured_at_us: 0,
+            frame_number,
+        }
+    }
+
+    struct Seen {
+        slot: Arc<FrameSlot>,
+        frames: Mutex<Vec<u64>>,
+    }
+
+    // Removes itself after the first frame, which must not deadlock
+    unsafe extern "C" fn once(frame: *const RtspFrame, user_data: *mut c_void) {
+        let (frame, seen) = unsafe { (&*frame, &*(user_data as *const Seen)) };
+        seen.frames.lock().unwrap().push(frame.frame_number);
+        let delivery = FrameDelivery { shared: Arc::clone(&seen.slot), subscription: 0 };
+        delivery.set_callback(None, ptr::null_mut());
+    }
+
+    #[test]
+    fn test_callback_can_remove_itself() {
+        let slot = Arc::new(FrameSlot::default());
+        let seen = Seen { slot: Arc::clone(&slot), frames: Mutex::new(Vec::new()) };
+        let user_data = &seen as *const Seen as *mut c_void;
+        slot.slot.lock().unwrap().registration = Some(Registration { callback: once, user_data });
+
+        slot.deliver(&frame(1));
+        slot.deliver(&frame(2));
+        assert_eq!(*seen.frames.lock().unwrap(), vec![1]);
+        assert!(slot.slot.lock().unwrap().delivering.is_none());
+    }
+
+    #[test]
+    fn test_timestamps_in_micros() {
+        assert_eq!(micros(Duration::from_millis(40)), 40_000);
+        assert_eq!(micros(Duration::MAX), i64::MAX);
+    }
+}
@@ -1,14 +1,605 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+//! C ABI of the RTSP player.
+//!
+//! Every function takes an opaque `RtspPlayerHandle` created by
+//! `rtsp_player_create` and released by `rtsp_player_destroy`. Panics never
+//! cross the boundary; a function that panics returns its failure value.
+//!
+//! A failing call returns false or NULL and leaves the reason in the calling
+//! thread's last error, see `rtsp_player_get_last_error_code`. Every call
+//! except the last-error getters clears it first.
+//!
+//! The library is called `rtsp_player` on every platform (`rtsp_player.dll`,
+//! `librtsp_player.so`, `librtsp_player.dylib`).
+//!
+//! The C header `include/rtsp_player.h` is generated with
+//! `cbindgen --config cbindgen.toml --output include/rtsp_player.h` from this
+//! crate's directory and committed, so consumers don't need a Rust toolchain.
+
+mod error;
+mod events;
+mod frames;
+mod stats;
+
+use crate::error::FfiError;
+use crate::events::EventDelivery;
+use crate::frames::FrameDelivery;
+use player::{ImageFormat, PixelFormat, PlayerError, RtspPlayer, RtspPlayerConfig, Transport};
+use std::sync::Mutex;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+use std::time::Duration;
+
+pub const RTSP_ERROR_OK: i32 = 0;
+pub const RTSP_ERROR_INIT: i32 = 1;
+pub const RTSP_ERROR_INVALID_CONFIG: i32 = 2;
+pub const RTSP_ERROR_AUTHENTICATION: i32 = 3;
+pub const RTSP_ERROR_CONNECTION: i32 = 4;
+pub const RTSP_ERROR_TIMEOUT: i32 = 5;
+pub const RTSP_ERROR_UNSUPPORTED_CODEC: i32 = 6;
+pub const RTSP_ERROR_MISSING_PLUGIN: i32 = 7;
+pub const RTSP_ERROR_STATE_CHANGE: i32 = 8;
+pub const RTSP_ERROR_SINK: i32 = 9;
+pub const RTSP_ERROR_STREAM: i32 = 10;
+pub const RTSP_ERROR_WINDOWS: i32 = 11;
+pub const RTSP_ERROR_RECORDING: i32 = 12;
+pub const RTSP_ERROR_SNAPSHOT: i32 = 13;
+pub const RTSP_ERROR_LIMIT_REACHED: i32 = 14;
+pub const RTSP_ERROR_INVALID_ARGUMENT: i32 = 15;
+pub const RTSP_ERROR_PANIC: i32 = 16;
+
+pub const RTSP_TRANSPORT_AUTO: u32 = 0;
+pub const RTSP_TRANSPORT_UDP: u32 = 1;
+pub const RTSP_TRANSPORT_UDP_MULTICAST: u32 = 2;
+pub const RTSP_TRANSPORT_TCP: u32 = 3;
+pub const RTSP_TRANSPORT_HTTP: u32 = 4;
+
+pub const RTSP_PIXEL_FORMAT_RGB: u32 = 0;
+pub const RTSP_PIXEL_FORMAT_RGBA: u32 = 1;
+pub const RTSP_PIXEL_FORMAT_BGR: u32 = 2;
+pub const RTSP_PIXEL_FORMAT_BGRA: u32 = 3;
+pub const RTSP_PIXEL_FORMAT_GRAY8: u32 = 4;
+
+pub use crate::events::*;
+pub use crate::frames::*;
+pub use crate::stats::*;
+
+/// Opaque player handle.
+pub struct RtspPlayerHandle {
+    // Declared first so the callbacks are gone before the player is dropped
+    events: Mutex<Option<EventDelivery>>,
+    frames: Mutex<Option<FrameDelivery>>,
+    player: RtspPlayer,
+}
+
+/// Settings for `rtsp_player_create_with_options`. Initialize with
+/// `rtsp_player_options_init` so fields added later get their defaults.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RtspPlayerOptions {
+    pub latency_ms: u32,
+    /// One of the `RTSP_TRANSPORT_*` constants.
+    pub transport: u32,
+    /// Decode without opening a window.
+    pub headless: bool,
+    pub audio: bool,
+    /// NULL for streams without authentication.
+    pub username: *const c_char,
+    pub password: *const c_char,
+    /// Name used in logs instead of the URL, may be NULL.
+    pub name: *const c_char,
+    /// Decode into memory for `rtsp_player_set_frame_callback` instead of a
+    /// window. Implies `headless` and no audio.
+    pub frames: bool,
+    /// `RTSP_PIXEL_FORMAT_*` of the frames passed to the frame callback.
+    pub frame_format: u32,
+}
+
+impl Default for RtspPlayerOptions {
+    fn default() -> Self {
+        RtspPlayerOptions {
+            latency_ms: 100,
+            transport: RTSP_TRANSPORT_AUTO,
+            headless: false,
+            audio: true,
+            username: ptr::null(),
+            password: ptr::null(),
+            name: ptr::null(),
+            frames: false,
+            frame_format: RTSP_PIXEL_FORMAT_RGBA,
+        }
+    }
+}
+
+/// A decoded frame copied out of the player, released with
+/// `rtsp_player_snapshot_free`. Rows are tightly packed.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RtspSnapshot {
+    pub data: *mut u8,
+    pub len: usize,
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+    /// One of the `RTSP_PIXEL_FORMAT_*` constants.
+    pub format: u32,
+}
+
+/// Run `body` with a fresh last error, storing the error or panic it ends
+/// with and returning `fallback` in that case.
+fn guard<T>(fallback: T, body: impl FnOnce() -> std::result::Result<T, FfiError>) -> T {
+    error::clear_last_error();
+    let error = match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(payload) => FfiError::panic(payload.as_ref()),
+    };
+    error::set_last_error(error);
+    fallback
+}
+
+unsafe fn handle<'a>(handle: *const RtspPlayerHandle) -> std::result::Result<&'a RtspPlayerHandle, FfiError> {
+    unsafe { handle.as_ref() }.ok_or_else(|| FfiError::invalid_argument("Player handle is NULL"))
+}
+
+unsafe fn player<'a>(handle: *const RtspPlayerHandle) -> std::result::Result<&'a RtspPlayer, FfiError> {
+    unsafe { self::handle(handle) }.map(|handle| &handle.player)
+}
+
+unsafe fn optional_string<'a>(text: *const c_char, name: &str) -> std::result::Result<Option<&'a str>, FfiError> {
+    if text.is_null() {
+        return Ok(None);
+    }
+    unsafe { CStr::from_ptr(text) }
+        .to_str()
+        .map(Some)
+        .map_err(|_| FfiError::invalid_argument(&format!("{} is not valid UTF-8", name)))
+}
+
+unsafe fn string<'a>(text: *const c_char, name: &str) -> std::result::Result<&'a str, FfiError> {
+    unsafe { optional_string(text, name) }?.ok_or_else(|| FfiError::invalid_argument(&format!("{} is NULL", name)))
+}
+
+fn transport(value: u32) -> std::result::Result<Transport, FfiError> {
+    match value {
+        RTSP_TRANSPORT_AUTO => Ok(Transport::Auto),
+        RTSP_TRANSPORT_UDP => Ok(Transport::UdpUnicast),
+        RTSP_TRANSPORT_UDP_MULTICAST => Ok(Transport::UdpMulticast),
+        RTSP_TRANSPORT_TCP => Ok(Transport::TcpInterleaved),
+        RTSP_TRANSPORT_HTTP => Ok(Transport::HttpTunnel),
+        _ => Err(FfiError::invalid_argument(&format!("Unknown transport {}", value))),
+    }
+}
+
+fn pixel_format(value: u32) -> std::result::Result<PixelFormat, FfiError> {
+    match value {
+        RTSP_PIXEL_FORMAT_RGB => Ok(PixelFormat::Rgb),
+        RTSP_PIXEL_FORMAT_RGBA => Ok(PixelFormat::Rgba),
+        RTSP_PIXEL_FORMAT_BGR => Ok(PixelFormat::Bgr),
+        RTSP_PIXEL_FORMAT_BGRA => Ok(PixelFormat::Bgra),
+        RTSP_PIXEL_FORMAT_GRAY8 => Ok(PixelFormat::Gray8),
+        _ => Err(FfiError::invalid_argument(&format!("Unknown pixel format {}", value))),
+    }
+}
+
+fn pixel_format_code(format: PixelFormat) -> u32 {
+    match format {
+        PixelFormat::Rgb => RTSP_PIXEL_FORMAT_RGB,
+        PixelFormat::Rgba => RTSP_PIXEL_FORMAT_RGBA,
+        PixelFormat::Bgr => RTSP_PIXEL_FORMAT_BGR,
+        PixelFormat::Bgra => RTSP_PIXEL_FORMAT_BGRA,
+        PixelFormat::Gray8 => RTSP_PIXEL_FORMAT_GRAY8,
+    }
+}
+
+fn into_handle(player: RtspPlayer) -> *mut RtspPlayerHandle {
+    Box::into_raw(Box::new(RtspPlayerHandle { events: Mutex::new(None), frames: Mutex::new(None), player }))
+}
+
+/// Fill `options` with the defaults `rtsp_player_create` uses.
+///
+/// # Safety
+/// `options` must be NULL or point to writable memory for an `RtspPlayerOptions`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_options_init(options: *mut RtspPlayerOptions) {
+    guard((), || {
+        if options.is_null() {
+            return Err(FfiError::invalid_argument("Options are NULL"));
+        }
+        unsafe { options.write(RtspPlayerOptions::default()) };
+        Ok(())
+    })
+}
+
+/// Create a stopped player for `url`. Returns NULL on failure.
+///
+/// # Safety
+/// `url` must be NULL or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_create(url: *const c_char) -> *mut RtspPlayerHandle {
+    guard(ptr::null_mut(), || {
+        let url = unsafe { string(url, "URL") }?;
+        Ok(into_handle(RtspPlayer::new(url)?))
+    })
+}
+
+/// Create a stopped player for `url` with `options`. Returns NULL on failure.
+///
+/// # Safety
+/// `url` must be a NUL-terminated string and `options` NULL or a valid
+/// `RtspPlayerOptions` whose strings are NULL or NUL-terminated.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_create_with_options(
+    url: *const c_char,
+    options: *const RtspPlayerOptions,
+) -> *mut RtspPlayerHandle {
+    guard(ptr::null_mut(), || {
+        let url = unsafe { string(url, "URL") }?;
+        let options = unsafe { options.as_ref() }.copied().unwrap_or_default();
+
+        let mut builder = RtspPlayerConfig::builder(url)
+            .latency(Duration::from_millis(options.latency_ms as u64))
+            .transport(transport(options.transport)?)
+            .headless(options.headless)
+            .audio(options.audio);
+        if let Some(username) = unsafe { optional_string(options.username, "Username") }? {
+            let password = unsafe { optional_string(options.password, "Password") }?.unwrap_or("");
+            builder = builder.credentials(username, password);
+        }
+        if let Some(name) = unsafe { optional_string(options.name, "Name") }? {
+            builder = builder.name(name);
+        }
+        if options.frames {
+            builder = builder.frames_only(pixel_format(options.frame_format)?);
+        }
+        Ok(into_handle(RtspPlayer::with_config(builder.build()?)?))
+    })
+}
+
+/// Stop the player and release the handle. NULL is ignored.
+///
+/// # Safety
+/// `handle` must come from `rtsp_player_create*` and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_destroy(handle: *mut RtspPlayerHandle) {
+    guard((), || {
+        if !handle.is_null() {
+            drop(unsafe { Box::from_raw(handle) });
+        }
+        Ok(())
+    })
+}
+
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_play(handle: *mut RtspPlayerHandle) -> bool {
+    guard(false, || {
+        unsafe { player(handle) }?.play()?;
+        Ok(true)
+    })
+}
+
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_pause(handle: *mut RtspPlayerHandle) -> bool {
+    guard(false, || {
+        unsafe { player(handle) }?.pause()?;
+        Ok(true)
+    })
+}
+
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_stop(handle: *mut RtspPlayerHandle) -> bool {
+    guard(false, || {
+        unsafe { player(handle) }?.stop()?;
+        Ok(true)
+    })
+}
+
+/// Seek to `position`, a fraction of the duration between 0.0 and 1.0.
+/// Live streams without a duration ignore it.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_seek(handle: *mut RtspPlayerHandle, position: f64) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        if !(0.0..=1.0).contains(&position) {
+            return Err(FfiError::invalid_argument("Seek position must be between 0.0 and 1.0"));
+        }
+        player.seek(position)?;
+        Ok(true)
+    })
+}
+
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_is_playing(handle: *mut RtspPlayerHandle) -> bool {
+    guard(false, || Ok(unsafe { player(handle) }?.is_playing()))
+}
+
+/// Render into a native window (HWND on Windows, X11 window id elsewhere).
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, `hwnd` a window that outlives the player.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_set_hwnd(handle: *mut RtspPlayerHandle, hwnd: *mut c_void) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        if hwnd.is_null() {
+            return Err(FfiError::invalid_argument("Window handle is NULL"));
+        }
+        player.set_window_handle(hwnd as usize)?;
+        Ok(true)
+    })
+}
+
+/// Copy the last rendered frame into `snapshot` in the given
+/// `RTSP_PIXEL_FORMAT_*`. Release it with `rtsp_player_snapshot_free`.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, `snapshot` NULL or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_snapshot(handle: *mut RtspPlayerHandle, format: u32, snapshot: *mut RtspSnapshot) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        let format = pixel_format(format)?;
+        if snapshot.is_null() {
+            return Err(FfiError::invalid_argument("Snapshot is NULL"));
+        }
+        let frame = player.snapshot_as(format)?;
+
+        let data = Box::into_raw(frame.data.into_boxed_slice());
+        let result = RtspSnapshot {
+            data: data.cast(),
+            len: data.len(),
+            width: frame.width,
+            height: frame.height,
+            stride: frame.width * format.bytes_per_pixel() as u32,
+            format: pixel_format_code(frame.format),
+        };
+        unsafe { snapshot.write(result) };
+        Ok(true)
+    })
+}
+
+/// Release the pixel data of a snapshot and reset it. Safe to call twice.
+///
+/// # Safety
+/// `snapshot` must be NULL or filled by `rtsp_player_snapshot`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_snapshot_free(snapshot: *mut RtspSnapshot) {
+    guard((), || {
+        let Some(snapshot) = (unsafe { snapshot.as_mut() }) else { return Ok(()) };
+        if !snapshot.data.is_null() {
+            drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(snapshot.data, snapshot.len)) });
+        }
+        snapshot.data = ptr::null_mut();
+        snapshot.len = 0;
+        Ok(())
+    })
+}
+
+/// Save the last rendered frame as JPEG (`.jpg`, `.jpeg`) or PNG.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, `path` NULL or a NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_snapshot_to_file(handle: *mut RtspPlayerHandle, path: *const c_char) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        let path = PathBuf::from(unsafe { string(path, "Path") }?);
+        player.snapshot_to_file(&path, ImageFormat::from_path(&path))?;
+        Ok(true)
+    })
+}
+
+/// Record to `path` without re-encoding, the container follows the extension.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, `path` NULL or a NUL-terminated UTF-8 string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_start_recording(handle: *mut RtspPlayerHandle, path: *const c_char) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        player.start_recording(unsafe { string(path, "Path") }?)?;
+        Ok(true)
+    })
+}
+
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_stop_recording(handle: *mut RtspPlayerHandle) -> bool {
+    guard(false, || {
+        unsafe { player(handle) }?.stop_recording()?;
+        Ok(true)
+    })
+}
+
+/// Copy the latest statistics into `stats`. They are refreshed once per
+/// stats interval, one second by default.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, `stats` NULL or writable.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_get_stats(handle: *mut RtspPlayerHandle, stats: *mut RtspStats) -> bool {
+    guard(false, || {
+        let player = unsafe { player(handle) }?;
+        if stats.is_null() {
+            return Err(FfiError::invalid_argument("Stats are NULL"));
+        }
+        unsafe { stats.write(RtspStats::from(&player.stats())) };
+        Ok(true)
+    })
+}
+
+/// Call `callback` with `user_data` for every player event, replacing the
+/// previous callback. NULL removes it.
+///
+/// Events are delivered in order on a thread owned by the player, one at a
+/// time. Once this function or `rtsp_player_destroy` returns, the previous
+/// callback is not running and will not be called again, so its `user_data`
+/// may be freed. Called from inside the callback, neither waits; the change
+/// applies from the next event.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle. `user_data` must stay valid until
+/// the callback is replaced or the player destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_set_event_callback(
+    handle: *mut RtspPlayerHandle,
+    callback: RtspEventCallback,
+    user_data: *mut c_void,
+) -> bool {
+    guard(false, || {
+        let handle = unsafe { self::handle(handle) }?;
+        let mut events = handle.events.lock().unwrap();
+        if events.is_none() {
+            if callback.is_none() {
+                return Ok(true);
+            }
+            let delivery = EventDelivery::spawn(&handle.player)
+                .map_err(|err| PlayerError::InitError(format!("Failed to start event thread: {}", err)))?;
+            *events = Some(delivery);
+        }
+        if let Some(delivery) = events.as_ref() {
+            delivery.set_callback(callback, user_data);
+        }
+        Ok(true)
+    })
+}
+
+/// Call `callback` with `user_data` for every decoded frame, replacing the
+/// previous callback. NULL removes it. The player must be created with the
+/// `frames` option.
+///
+/// The frame and its pixel data are lent without copying and only valid
+/// until the callback returns; copy what you need to keep. Frames arrive on a
+/// thread owned by the player, one at a time. A slow callback makes the
+/// player skip frames for it rather than stall the stream. Replacing the
+/// callback waits for a running call like `rtsp_player_set_event_callback`.
+///
+/// # Safety
+/// `handle` must be NULL or a live handle. `user_data` must stay valid until
+/// the callback is replaced or the player destroyed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_set_frame_callback(
+    handle: *mut RtspPlayerHandle,
+    callback: RtspFrameCallback,
+    user_data: *mut c_void,
+) -> bool {
+    guard(false, || {
+        let handle = unsafe { self::handle(handle) }?;
+        let mut frames = handle.frames.lock().unwrap();
+        if callback.is_none() {
+            if let Some(delivery) = frames.take() {
+                delivery.unsubscribe(&handle.player);
+            }
+            return Ok(true);
+        }
+        if frames.is_none() {
+            *frames = Some(FrameDelivery::subscribe(&handle.player)?);
+        }
+        if let Some(delivery) = frames.as_ref() {
+            delivery.set_callback(callback, user_data);
+        }
+        Ok(true)
+    })
+}
+
+/// `RTSP_ERROR_*` code of the last failed call on this thread,
+/// `RTSP_ERROR_OK` if the last call succeeded.
+#[unsafe(no_mangle)]
+pub extern "C" fn rtsp_player_get_last_error_code() -> i32 {
+    catch_unwind(error::last_error_code).map_or(RTSP_ERROR_PANIC, |code| code as i32)
+}
+
+/// Message of the last failed call on this thread, NULL if the last call
+/// succeeded. Release it with `rtsp_player_free_string`.
+#[unsafe(no_mangle)]
+pub extern "C" fn rtsp_player_get_last_error() -> *mut c_char {
+    catch_unwind(error::last_error_message)
+        .ok()
+        .flatten()
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Release a string returned by this library. NULL is ignored.
+///
+/// # Safety
+/// `text` must be NULL or come from `rtsp_player_get_last_error`, and be freed once.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn rtsp_player_free_string(text: *mut c_char) {
+    if !text.is_null() {
+        drop(unsafe { CString::from_raw(text) });
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use player::ErrorCode;
+
+    #[test]
+    fn test_null_handles_are_rejected() {
+        unsafe {
+            assert!(rtsp_player_create(ptr::null()).is_null());
+            assert_eq!(rtsp_player_get_last_error_code(), RTSP_ERROR_INVALID_ARGUMENT);
+            assert!(!rtsp_player_play(ptr::null_mut()));
+            assert!(!rtsp_player_seek(ptr::null_mut(), 0.5));
+            assert!(!rtsp_player_set_frame_callback(ptr::null_mut(), None, ptr::null_mut()));
+            rtsp_player_destroy(ptr::null_mut());
+            assert_eq!(rtsp_player_get_last_error_code(), RTSP_ERROR_OK);
+            rtsp_player_snapshot_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_last_error_message() {
+        unsafe {
+            assert!(!rtsp_player_stop(ptr::null_mut()));
+            let message = rtsp_player_get_last_error();
+            assert_eq!(CStr::from_ptr(message).to_str().unwrap(), "Player handle is NULL");
+            rtsp_player_free_string(message);
+        }
+    }
+
+    #[test]
+    fn test_panics_do_not_escape() {
+        assert!(!guard(false, || panic!("boom")));
+        assert_eq!(rtsp_player_get_last_error_code(), RTSP_ERROR_PANIC);
+    }
+
+    #[test]
+    fn test_error_constants_match_codes() {
+        assert_eq!(RTSP_ERROR_LIMIT_REACHED, ErrorCode::LimitReached as i32);
+        assert_eq!(RTSP_ERROR_INVALID_ARGUMENT, ErrorCode::InvalidArgument as i32);
+        assert_eq!(RTSP_ERROR_PANIC, ErrorCode::Panic as i32);
+    }
 
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn test_snapshot_free_resets() {
+        let data = Box::into_raw(vec![0u8; 12].into_boxed_slice());
+        let mut snapshot = RtspSnapshot {
+            data: data.cast(),
+            len: 12,
+            width: 2,
+            height: 2,
+            stride: 6,
+            format: RTSP_PIXEL_FORMAT_RGB,
+        };
+        unsafe {
+            rtsp_player_snapshot_free(&mut snapshot);
+            rtsp_player_snapshot_free(&mut snapshot);
+        }
+        assert!(snapshot.data.is_null());
     }
 }
@@ -0,0 +1,71 @@
+use player::StreamStats;
+
+/// Playback statistics, see `rtsp_player_get_stats`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RtspStats {
+    /// -1 when unknown.
+    pub position_ms: i64,
+    /// -1 for live streams.
+    pub duration_ms: i64,
+    /// Attempts since the stream last reached playing.
+    pub reconnect_attempts: u32,
+    /// Attempts since the player was created.
+    pub reconnects_total: u64,
+    pub stalls: u64,
+    pub uptime_ms: u64,
+    pub bitrate_bps: u64,
+    pub fps: f64,
+    pub frames_decoded: u64,
+    pub frames_rendered: u64,
+    pub frames_dropped: u64,
+    pub qos_dropped: u64,
+    pub jitterbuffer_lost: u64,
+    pub jitterbuffer_late: u64,
+    pub packets_received: u64,
+    /// Negative when duplicates outnumber losses.
+    pub packets_lost: i64,
+    pub jitter_ms: f64,
+}
+
+impl From<&StreamStats> for RtspStats {
+    fn from(stats: &StreamStats) -> Self {
+        let millis = |duration: std::time::Duration| i64::try_from(duration.as_millis()).unwrap_or(i64::MAX);
+        RtspStats {
+            position_ms: stats.position.map_or(-1, millis),
+            duration_ms: stats.duration.map_or(-1, millis),
+            reconnect_attempts: stats.reconnect_attempts,
+            reconnects_total: stats.reconnects_total,
+            stalls: stats.stalls,
+            uptime_ms: millis(stats.uptime) as u64,
+            bitrate_bps: stats.bitrate_bps,
+            fps: stats.fps,
+            frames_decoded: stats.frames_decoded,
+            frames_rendered: stats.frames_rendered,
+            frames_dropped: stats.frames_dropped,
+            qos_dropped: stats.qos_dropped,
+            jitterbuffer_lost: stats.jitterbuffer.lost,
+            jitterbuffer_late: stats.jitterbuffer.late,
+            packets_received: stats.rtp.packets_received,
+            packets_lost: stats.rtp.packets_lost,
+            jitter_ms: stats.rtp.jitter.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_unknown_times_are_negative() {
+        let stats = StreamStats {
+            position: Some(Duration::from_millis(1500)),
+            uptime: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let c_stats = RtspStats::from(&stats);
+        assert_eq!((c_stats.position_ms, c_stats.duration_ms, c_stats.uptime_ms), (1500, -1, 2000));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::PlayerError;
+use gstreamer as gst;
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A GLib main loop running on its own thread.
+///
+/// Bus watches are attached to this loop's context instead of the global
+/// default one, so messages are dispatched whether or not the host application
+/// runs a GLib main loop (the Win32 message loop does not).
+#[derive(Debug)]
+pub(crate) struct BusDispatcher {
+    context: glib::MainContext,
+    main_loop: glib::MainLoop,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BusDispatcher {
+    pub(crate) fn spawn(name: &str) -> std::result::Result<Self, PlayerError> {
+        let context = glib::MainContext::new();
+        let main_loop = glib::MainLoop::new(Some(&context), false);
+
+        let thread = std::thread::Builder::new().name(name.to_string()).spawn({
+            let context = context.clone();
+            let main_loop = main_loop.clone();
+            move || {
+                if let Err(err) = context.with_thread_default(|| main_loop.run()) {
+                    println!("Bus dispatcher could not acquire its main context: {}", err);
+                }
+            }
+        })
+        .map_err(|err| PlayerError::InitError(format!("Failed to start bus thread: {}", err)))?;
+
+        Ok(BusDispatcher {
+            context,
+            main_loop,
+            thread: Mutex::new(Some(thread)),
+        })
+    }
+
+    /// Dispatch the messages of `bus` to `func` on the dispatcher thread.
+    ///
+    /// The watch stays active until the returned source is destroyed or `func`
+    /// returns `ControlFlow::Break`.
+    pub(crate) fn watch_bus<F>(&self, bus: &gst::Bus, func: F) -> glib::Source
+    where
+        F: FnMut(&gst::Bus, &gst::Message) -> glib::ControlFlow + Send + 'static,
+    {
+        let source = bus.create_watch(Some("rtsp-player-bus"), glib::Priority::DEFAULT, func);
+        source.attach(Some(&self.context));
+        source
+    }
+
+    /// Call `func` every `interval` on the dispatcher thread until it returns
+    /// `ControlFlow::Break` or the returned source is destroyed.
+    pub(crate) fn schedule<F>(&self, interval: Duration, func: F) -> glib::Source
+    where
+        F: FnMut() -> glib::ControlFlow + Send + 'static,
+    {
+        let source = glib::timeout_source_new(interval, Some("rtsp-player-timer"), glib::Priority::DEFAULT, func);
+        source.attach(Some(&self.context));
+        source
+    }
+
+    /// Run `func` once on the dispatcher thread, e.g. to emit events from a
+    /// streaming thread.
+    pub(crate) fn invoke<F>(&self, func: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.context.invoke(func);
+    }
+}
+
+impl Drop for BusDispatcher {
+    fn drop(&mut self) {
+        // Quit from inside the loop so a quit racing with startup is not lost
+        let main_loop = self.main_loop.clone();
+        self.context.invoke(move || main_loop.quit());
+
+        if let Some(thread) = self.thread.lock().unwrap().take()
+            && thread.thread().id() != std::thread::current().id()
+        {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::redact::redact_text;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::error::Error;
+use std::fmt;
+
+/// Stable numeric error codes, shared with the C ABI. Never renumber these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum ErrorCode {
+    Ok = 0,
+    Init = 1,
+    InvalidConfig = 2,
+    Authentication = 3,
+    Connection = 4,
+    Timeout = 5,
+    UnsupportedCodec = 6,
+    MissingPlugin = 7,
+    StateChange = 8,
+    Sink = 9,
+    Stream = 10,
+    Windows = 11,
+    Recording = 12,
+    Snapshot = 13,
+    LimitReached = 14,
+    /// A NULL handle or malformed argument passed to the C ABI.
+    InvalidArgument = 15,
+    /// A panic caught at the C ABI boundary.
+    Panic = 16,
+}
+
+/// What GStreamer reported alongside an error: the `GError` domain and code,
+/// its message, the debug string and the element that posted it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GstErrorInfo {
+    pub domain: String,
+    pub code: i32,
+    pub message: String,
+    pub debug: Option<String>,
+    pub source: Option<String>,
+}
+
+impl GstErrorInfo {
+    /// Details for an error raised by the player itself rather than GStreamer.
+    pub(crate) fn local(message: impl Into<String>) -> Self {
+        GstErrorInfo {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    fn from_message(msg: &gst::message::Error) -> Self {
+        let err = msg.error();
+        GstErrorInfo {
+            domain: err.domain().as_str().to_string(),
+            code: gst_error_code(&err),
+            message: redact_text(err.message()),
+            debug: msg.debug().map(|debug| redact_text(&debug)),
+            source: msg.src().map(|src| src.path_string().to_string()),
+        }
+    }
+
+    fn mentions_timeout(&self) -> bool {
+        let text = format!("{} {}", self.message, self.debug.as_deref().unwrap_or("")).to_lowercase();
+        text.contains("timeout") || text.contains("timed out") || text.contains("could not receive any")
+    }
+}
+
+impl fmt::Display for GstErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(source) = &self.source {
+            write!(f, " (from {})", source)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerError {
+    InitError(String),
+    ConfigError(String),
+    /// The server rejected the credentials (RTSP 401).
+    AuthenticationError(GstErrorInfo),
+    /// Name resolution or the TCP connection to the server failed.
+    ConnectionError(GstErrorInfo),
+    TimeoutError(GstErrorInfo),
+    UnsupportedCodec(GstErrorInfo),
+    MissingPlugin(GstErrorInfo),
+    StateChangeError(String),
+    SinkError(GstErrorInfo),
+    /// Any other error posted on the pipeline bus.
+    StreamError(GstErrorInfo),
+    WindowsError(String),
+    /// Starting, writing or finalizing a recording failed.
+    RecordingError(String),
+    /// No frame was available or it could not be converted.
+    SnapshotError(String),
+    /// A resource limit, e.g. the number of concurrent decoders, was reached.
+    LimitReached(String),
+}
+
+impl PlayerError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            PlayerError::InitError(_) => ErrorCode::Init,
+            PlayerError::ConfigError(_) => ErrorCode::InvalidConfig,
+            PlayerError::AuthenticationError(_) => ErrorCode::Authentication,
+            PlayerError::ConnectionError(_) => ErrorCode::Connection,
+            PlayerError::TimeoutError(_) => ErrorCode::Timeout,
+            PlayerError::UnsupportedCodec(_) => ErrorCode::UnsupportedCodec,
+            PlayerError::MissingPlugin(_) => ErrorCode::MissingPlugin,
+            PlayerError::StateChangeError(_) => ErrorCode::StateChange,
+            PlayerError::SinkError(_) => ErrorCode::Sink,
+            PlayerError::StreamError(_) => ErrorCode::Stream,
+            PlayerError::WindowsError(_) => ErrorCode::Windows,
+            PlayerError::RecordingError(_) => ErrorCode::Recording,
+            PlayerError::SnapshotError(_) => ErrorCode::Snapshot,
+            PlayerError::LimitReached(_) => ErrorCode::LimitReached,
+        }
+    }
+
+    /// GStreamer details, present for errors that came from the pipeline.
+    pub fn gst_info(&self) -> Option<&GstErrorInfo> {
+        match self {
+            PlayerError::AuthenticationError(info)
+            | PlayerError::ConnectionError(info)
+            | PlayerError::TimeoutError(info)
+            | PlayerError::UnsupportedCodec(info)
+            | PlayerError::MissingPlugin(info)
+            | PlayerError::SinkError(info)
+            | PlayerError::StreamError(info) => Some(info),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn missing_element(factory: &str) -> Self {
+        PlayerError::MissingPlugin(GstErrorInfo {
+            domain: gst::CoreError::domain().as_str().to_string(),
+            code: gst::CoreError::MissingPlugin.code(),
+            message: format!("Missing element '{}', check your GStreamer plugin installation", factory),
+            debug: None,
+            source: None,
+        })
+    }
+
+    /// Classify an error posted on the pipeline bus.
+    pub(crate) fn from_error_message(msg: &gst::message::Error) -> Self {
+        let err = msg.error();
+        let info = GstErrorInfo::from_message(msg);
+
+        if let Some(kind) = err.kind::<gst::ResourceError>() {
+            return match kind {
+                gst::ResourceError::NotAuthorized => PlayerError::AuthenticationError(info),
+                _ if info.mentions_timeout() => PlayerError::TimeoutError(info),
+                gst::ResourceError::NotFound
+                | gst::ResourceError::OpenRead
+                | gst::ResourceError::OpenWrite
+                | gst::ResourceError::OpenReadWrite => PlayerError::ConnectionError(info),
+                _ if is_video_sink(&info) => PlayerError::SinkError(info),
+                _ => PlayerError::StreamError(info),
+            };
+        }
+        if let Some(gst::CoreError::MissingPlugin) = err.kind::<gst::CoreError>() {
+            return PlayerError::MissingPlugin(info);
+        }
+        if matches!(
+            err.kind::<gst::StreamError>(),
+            Some(
+                gst::StreamError::CodecNotFound
+                    | gst::StreamError::Decode
+                    | gst::StreamError::TypeNotFound
+                    | gst::StreamError::WrongType
+                    | gst::StreamError::Format
+            )
+        ) {
+            return PlayerError::UnsupportedCodec(info);
+        }
+        if is_video_sink(&info) {
+            return PlayerError::SinkError(info);
+        }
+        PlayerError::StreamError(info)
+    }
+}
+
+impl fmt::Display for PlayerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerError::InitError(msg) => write!(f, "Initialization error: {}", msg),
+            PlayerError::ConfigError(msg) => write!(f, "Invalid configuration: {}", msg),
+            PlayerError::AuthenticationError(info) => write!(f, "Authentication failed: {}", info),
+            PlayerError::ConnectionError(info) => write!(f, "Connection error: {}", info),
+            PlayerError::TimeoutError(info) => write!(f, "Timed out: {}", info),
+            PlayerError::UnsupportedCodec(info) => write!(f, "Unsupported codec: {}", info),
+            PlayerError::MissingPlugin(info) => write!(f, "Missing plugin: {}", info),
+            PlayerError::StateChangeError(msg) => write!(f, "State change failed: {}", msg),
+            PlayerError::SinkError(info) => write!(f, "Video sink error: {}", info),
+            PlayerError::StreamError(info) => write!(f, "Stream error: {}", info),
+            PlayerError::WindowsError(msg) => write!(f, "Windows API error: {}", msg),
+            PlayerError::RecordingError(msg) => write!(f, "Recording error: {}", msg),
+            PlayerError::SnapshotError(msg) => write!(f, "Snapshot failed: {}", msg),
+            PlayerError::LimitReached(msg) => write!(f, "Limit reached: {}", msg),
+        }
+    }
+}
+
+impl Error for PlayerError {}
+
+impl From<glib::BoolError> for PlayerError {
+    fn from(err: glib::BoolError) -> Self {
+        PlayerError::InitError(err.to_string())
+    }
+}
+
+impl From<gst::StateChangeError> for PlayerError {
+    fn from(err: gst::StateChangeError) -> Self {
+        PlayerError::StateChangeError(err.to_string())
+    }
+}
+
+#[cfg(all(windows, feature = "win32-gui"))]
+impl From<windows::core::Error> for PlayerError {
+    fn from(err: windows::core::Error) -> Self {
+        PlayerError::WindowsError(err.to_string())
+    }
+}
+
+fn gst_error_code(err: &glib::Error) -> i32 {
+    if let Some(kind) = err.kind::<gst::CoreError>() {
+        kind.code()
+    } else if let Some(kind) = err.kind::<gst::LibraryError>() {
+        kind.code()
+    } else if let Some(kind) = err.kind::<gst::ResourceError>() {
+        kind.code()
+    } else if let Some(kind) = err.kind::<gst::StreamError>() {
+        kind.code()
+    } else {
+        -1
+    }
+}
+
+fn is_video_sink(info: &GstErrorInfo) -> bool {
+    info.source
+        .as_deref()
+        .map(|path| path.ends_with("/videosink"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_stable() {
+        assert_eq!(PlayerError::ConfigError("bad".into()).code() as i32, 2);
+        assert_eq!(PlayerError::AuthenticationError(GstErrorInfo::local("401")).code() as i32, 3);
+        assert_eq!(PlayerError::SinkError(GstErrorInfo::local("no overlay")).code() as i32, 9);
+        assert_eq!(PlayerError::WindowsError("x".into()).code() as i32, 11);
+    }
+
+    #[test]
+    fn test_timeout_detection() {
+        let info = GstErrorInfo {
+            debug: Some("Could not receive any UDP packets for 5.0000 seconds".into()),
+            ..GstErrorInfo::local("Could not read from resource.")
+        };
+        assert!(info.mentions_timeout());
+        assert!(!GstErrorInfo::local("Not found").mentions_timeout());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{AudioInfo, CertificateProblem, PlayerError, ReconnectReason, StreamStats, Transport, VideoInfo};
+use gstreamer as gst;
+use std::fmt;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Playback state of the pipeline, independent of GStreamer types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerState {
+    Stopped,
+    Ready,
+    Paused,
+    Playing,
+}
+
+impl From<gst::State> for PlayerState {
+    fn from(state: gst::State) -> Self {
+        match state {
+            gst::State::Playing => PlayerState::Playing,
+            gst::State::Paused => PlayerState::Paused,
+            gst::State::Ready => PlayerState::Ready,
+            _ => PlayerState::Stopped,
+        }
+    }
+}
+
+impl fmt::Display for PlayerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            PlayerState::Stopped => "Stopped",
+            PlayerState::Ready => "Ready",
+            PlayerState::Paused => "Paused",
+            PlayerState::Playing => "Playing",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Everything a player reports while running.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerEvent {
+    StateChanged { old: PlayerState, current: PlayerState },
+    StreamStarted,
+    Buffering(i32),
+    EndOfStream,
+    Error(PlayerError),
+    /// The server rejected the credentials (RTSP 401). Follows the `Error`
+    /// event; the player stops instead of reconnecting with the same credentials.
+    AuthenticationFailed { username: Option<String> },
+    /// The server certificate failed validation. When `accepted` is false the
+    /// connection was refused and the player stops instead of reconnecting.
+    CertificateError { problems: Vec<CertificateProblem>, accepted: bool },
+    /// A reconnect attempt will start after `delay`. `max_attempts` is `None`
+    /// when retrying forever.
+    Reconnecting { attempt: u32, max_attempts: Option<u32>, delay: Duration, reason: ReconnectReason },
+    Reconnected { attempts: u32 },
+    /// No RTP arrived over `from` within the fallback delay, the player
+    /// reconnects using `to` and keeps it from then on.
+    TransportFallback { from: Transport, to: Transport, waited: Duration },
+    /// The session is still open but no frame arrived for `idle`. A reconnect follows.
+    Stalled { idle: Duration },
+    /// Reconnection stopped after exhausting the retry budget, playback has stopped.
+    ReconnectGaveUp { attempts: u32 },
+    RecordingStarted { path: PathBuf },
+    /// The recording was finalized, or aborted after a write error.
+    RecordingStopped { path: PathBuf },
+    /// A segment of a segmented recording was completed.
+    SegmentClosed { path: PathBuf },
+    VideoInfo(VideoInfo),
+    AudioInfo(AudioInfo),
+    Stats(StreamStats),
+}
+
+/// Identifies a subscription for [`EventBus::unsubscribe`].
+pub type SubscriptionId = u64;
+
+type EventCallback = Arc<dyn Fn(&PlayerEvent) + Send + Sync>;
+
+enum Subscriber {
+    Channel(Sender<PlayerEvent>),
+    Callback(EventCallback),
+    Stream(tokio::sync::mpsc::UnboundedSender<PlayerEvent>),
+}
+
+#[derive(Default)]
+struct Subscribers {
+    next_id: SubscriptionId,
+    entries: Vec<(SubscriptionId, Subscriber)>,
+}
+
+/// Fan-out of player events to any number of independent subscribers.
+///
+/// Every subscriber sees every event emitted after it subscribed. Receivers
+/// that have been dropped are removed on the next emit.
+#[derive(Clone, Default)]
+pub(crate) struct EventBus {
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl EventBus {
+    pub(crate) fn emit(&self, event: PlayerEvent) {
+        let mut callbacks = Vec::new();
+        {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            subscribers.entries.retain(|(_, subscriber)| match subscriber {
+                Subscriber::Channel(sender) => sender.send(event.clone()).is_ok(),
+                Subscriber::Stream(sender) => sender.send(event.clone()).is_ok(),
+                Subscriber::Callback(callback) => {
+                    callbacks.push(Arc::clone(callback));
+                    true
+                }
+            });
+        }
+
+        // Run callbacks without holding the lock so they may (un)subscribe
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+
+    fn add(&self, subscriber: Subscriber) -> SubscriptionId {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.next_id += 1;
+        let id = subscribers.next_id;
+        subscribers.entries.push((id, subscriber));
+        id
+    }
+
+    pub(crate) fn subscribe(&self) -> EventReceiver {
+        let (sender, receiver) = channel();
+        self.add(Subscriber::Channel(sender));
+        EventReceiver { receiver }
+    }
+
+    pub(crate) fn on_event<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&PlayerEvent) + Send + Sync + 'static,
+    {
+        self.add(Subscriber::Callback(Arc::new(callback)))
+    }
+
+    pub(crate) fn stream(&self) -> EventStream {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        self.add(Subscriber::Stream(sender));
+        EventStream { receiver }
+    }
+
+    pub(crate) fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let before = subscribers.entries.len();
+        subscribers.entries.retain(|(entry_id, _)| *entry_id != id);
+        subscribers.entries.len() != before
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let count = self.subscribers.lock().map(|s| s.entries.len()).unwrap_or(0);
+        f.debug_struct("EventBus").field("subscribers", &count).finish()
+    }
+}
+
+/// Blocking receiver of player events. Iterating ends when the player is dropped.
+#[derive(Debug)]
+pub struct EventReceiver {
+    receiver: Receiver<PlayerEvent>,
+}
+
+impl EventReceiver {
+    /// Next event if one is pending, without blocking.
+    pub fn try_recv(&self) -> Option<PlayerEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next event.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<PlayerEvent> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
+
+impl Iterator for EventReceiver {
+    type Item = PlayerEvent;
+
+    fn next(&mut self) -> Option<PlayerEvent> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Async stream of player events, ends when the player is dropped.
+#[derive(Debug)]
+pub struct EventStream {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<PlayerEvent>,
+}
+
+impl futures_core::Stream for EventStream {
+    type Item = PlayerEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<PlayerEvent>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_every_subscriber_gets_every_event() {
+        let bus = EventBus::default();
+        let first = bus.subscribe();
+        let second = bus.subscribe();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = Arc::clone(&calls);
+        let id = bus.on_event(move |_| {
+            calls_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        bus.emit(PlayerEvent::StreamStarted);
+        bus.emit(PlayerEvent::Buffering(50));
+
+        assert_eq!(first.try_recv(), Some(PlayerEvent::StreamStarted));
+        assert_eq!(first.try_recv(), Some(PlayerEvent::Buffering(50)));
+        assert_eq!(second.try_recv(), Some(PlayerEvent::StreamStarted));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        assert!(bus.unsubscribe(id));
+        bus.emit(PlayerEvent::EndOfStream);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_dropped_receivers_are_removed() {
+        let bus = EventBus::default();
+        drop(bus.subscribe());
+        let _stream = bus.stream();
+        bus.emit(PlayerEvent::EndOfStream);
+        assert_eq!(bus.subscribers.lock().unwrap().entries.len(), 1);
+    }
+}
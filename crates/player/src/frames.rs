@@ -0,0 +1,347 @@
+use crate::events::SubscriptionId;
+use crate::snapshot::pack_rows;
+use crate::{PixelFormat, PlayerError};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch.
+const NTP_UNIX_OFFSET: Duration = Duration::from_secs(2_208_988_800);
+
+/// A decoded frame from the `appsink`.
+///
+/// The pixel data is the GStreamer buffer itself, mapped read-only and shared
+/// between all subscribers, so handing a frame out never copies it.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    buffer: Arc<gst::MappedBuffer<gst::buffer::Readable>>,
+    caps: gst::Caps,
+    offset: usize,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes between the starts of two rows, may include padding.
+    pub stride: usize,
+    pub format: PixelFormat,
+    /// Presentation timestamp in the stream's running time.
+    pub pts: Option<Duration>,
+    /// When the camera captured the frame according to its RTCP sender
+    /// reports, or when it left the decoder if the server sends none.
+    pub captured_at: SystemTime,
+    /// Counts the frames taken from the `appsink`, so gaps are frames this
+    /// subscriber fell behind on. Frames the sink itself drops when the
+    /// application is too slow are not counted.
+    pub frame_number: u64,
+}
+
+impl Frame {
+    /// Pixel data starting at the first row, `stride * height` bytes.
+    pub fn data(&self) -> &[u8] {
+        &self.buffer.as_slice()[self.offset..]
+    }
+
+    /// Copy of the pixel data with the row padding removed.
+    pub fn to_packed(&self) -> Vec<u8> {
+        let row_bytes = self.width as usize * self.format.bytes_per_pixel();
+        pack_rows(self.buffer.as_slice(), self.offset, self.stride, row_bytes, self.height as usize)
+            .unwrap_or_default()
+    }
+
+    /// The frame as a sample sharing the decoded memory, for feeding it into
+    /// another pipeline.
+    pub(crate) fn to_sample(&self) -> gst::Sample {
+        gst::Sample::builder()
+            .buffer(&self.buffer.buffer_owned())
+            .caps(&self.caps)
+            .build()
+    }
+
+    fn from_sample(sample: &gst::Sample, format: PixelFormat, frame_number: u64) -> Option<Frame> {
+        let caps = sample.caps_owned()?;
+        let info = gst_video::VideoInfo::from_caps(&caps).ok()?;
+        let buffer = sample.buffer_owned()?;
+        let pts = buffer.pts().map(Duration::from);
+        let captured_at = ntp_capture_time(&buffer).unwrap_or_else(SystemTime::now);
+        let buffer = buffer.into_mapped_buffer_readable().ok()?;
+
+        Some(Frame {
+            buffer: Arc::new(buffer),
+            caps,
+            offset: info.offset()[0],
+            width: info.width(),
+            height: info.height(),
+            stride: info.stride()[0] as usize,
+            format,
+            pts,
+            captured_at,
+            frame_number,
+        })
+    }
+}
+
+/// Capture time from the `timestamp/x-ntp` reference meta `rtspsrc` attaches.
+fn ntp_capture_time(buffer: &gst::BufferRef) -> Option<SystemTime> {
+    buffer
+        .iter_meta::<gst::ReferenceTimestampMeta>()
+        .find(|meta| meta.reference().structure(0).is_some_and(|s| s.name() == "timestamp/x-ntp"))
+        .and_then(|meta| Duration::from(meta.timestamp()).checked_sub(NTP_UNIX_OFFSET))
+        .map(|since_epoch| UNIX_EPOCH + since_epoch)
+}
+
+struct QueueState<T> {
+    items: VecDeque<T>,
+    dropped: u64,
+    closed: bool,
+}
+
+/// Bounded queue that drops its oldest entry when full, so a slow consumer
+/// only ever sees stale frames disappear and never blocks the pipeline.
+pub(crate) struct FrameQueue<T> {
+    state: Mutex<QueueState<T>>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+impl<T> FrameQueue<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        FrameQueue {
+            state: Mutex::new(QueueState { items: VecDeque::with_capacity(capacity), dropped: 0, closed: false }),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Returns `false` once the queue is closed.
+    pub(crate) fn push(&self, item: T) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+        if state.items.len() == self.capacity {
+            state.items.pop_front();
+            state.dropped += 1;
+        }
+        state.items.push_back(item);
+        self.ready.notify_one();
+        true
+    }
+
+    /// Wait for the next item, `None` once closed and drained.
+    pub(crate) fn pop(&self, timeout: Option<Duration>) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if let Some(item) = state.items.pop_front() {
+                return Some(item);
+            }
+            if state.closed {
+                return None;
+            }
+            state = match timeout {
+                Some(timeout) => {
+                    let (state, result) = self.ready.wait_timeout(state, timeout).unwrap();
+                    if result.timed_out() && state.items.is_empty() {
+                        return None;
+                    }
+                    state
+                }
+                None => self.ready.wait(state).unwrap(),
+            };
+        }
+    }
+
+    pub(crate) fn try_pop(&self) -> Option<T> {
+        self.state.lock().unwrap().items.pop_front()
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.state.lock().unwrap().dropped
+    }
+
+    pub(crate) fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.ready.notify_all();
+    }
+}
+
+/// Distributes frames from the `appsink` to every subscriber's queue.
+#[derive(Default)]
+pub(crate) struct FrameHub {
+    subscribers: Mutex<Vec<(SubscriptionId, Weak<FrameQueue<Frame>>)>>,
+    next_id: AtomicU64,
+    frame_number: AtomicU64,
+}
+
+impl FrameHub {
+    pub(crate) fn subscribe(&self, capacity: usize) -> (SubscriptionId, Arc<FrameQueue<Frame>>) {
+        let queue = Arc::new(FrameQueue::new(capacity));
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.subscribers.lock().unwrap().push((id, Arc::downgrade(&queue)));
+        (id, queue)
+    }
+
+    pub(crate) fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let Some(index) = subscribers.iter().position(|(entry_id, _)| *entry_id == id) else { return false };
+        let (_, queue) = subscribers.remove(index);
+        if let Some(queue) = queue.upgrade() {
+            queue.close();
+        }
+        true
+    }
+
+    /// Close every queue, ending all iterators and callback threads.
+    pub(crate) fn close(&self) {
+        for (_, queue) in self.subscribers.lock().unwrap().drain(..) {
+            if let Some(queue) = queue.upgrade() {
+                queue.close();
+            }
+        }
+    }
+
+    fn publish(&self, sample: &gst::Sample, format: PixelFormat) {
+        let frame_number = self.frame_number.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+        let Some(frame) = Frame::from_sample(sample, format, frame_number) else { return };
+        subscribers.retain(|(_, queue)| queue.upgrade().is_some_and(|queue| queue.push(frame.clone())));
+    }
+
+    /// Feed the frames reaching the pipeline's `appsink` into this hub.
+    pub(crate) fn attach(self: &Arc<Self>, pipeline: &gst::Pipeline, format: PixelFormat) -> std::result::Result<(), PlayerError> {
+        let appsink = pipeline
+            .by_name("videosink")
+            .and_then(|sink| sink.dynamic_cast::<gst_app::AppSink>().ok())
+            .ok_or_else(|| PlayerError::InitError("Frame delivery requires an appsink".into()))?;
+
+        let hub = Arc::downgrade(self);
+        appsink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_sample(move |appsink| {
+                    let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    if let Some(hub) = hub.upgrade() {
+                        hub.publish(&sample, format);
+                    }
+                    Ok(gst::FlowSuccess::Ok)
+                })
+                .build(),
+        );
+        Ok(())
+    }
+}
+
+/// Blocking iterator over decoded frames. Ends when the player is dropped.
+///
+/// Holds at most the configured number of frames; when the consumer falls
+/// behind the oldest are dropped, see [`FrameReceiver::dropped`].
+pub struct FrameReceiver {
+    id: SubscriptionId,
+    queue: Arc<FrameQueue<Frame>>,
+    hub: Weak<FrameHub>,
+}
+
+impl FrameReceiver {
+    pub(crate) fn new(hub: &Arc<FrameHub>, capacity: usize) -> Self {
+        let (id, queue) = hub.subscribe(capacity);
+        FrameReceiver { id, queue, hub: Arc::downgrade(hub) }
+    }
+
+    /// Next frame if one is queued, without blocking.
+    pub fn try_recv(&self) -> Option<Frame> {
+        self.queue.try_pop()
+    }
+
+    /// Wait up to `timeout` for the next frame.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Frame> {
+        self.queue.pop(Some(timeout))
+    }
+
+    /// Frames discarded because this receiver fell behind.
+    pub fn dropped(&self) -> u64 {
+        self.queue.dropped()
+    }
+}
+
+impl Iterator for FrameReceiver {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        self.queue.pop(None)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        if let Some(hub) = self.hub.upgrade() {
+            hub.unsubscribe(self.id);
+        }
+    }
+}
+
+impl std::fmt::Debug for FrameReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("FrameReceiver").field("id", &self.id).finish()
+    }
+}
+
+impl std::fmt::Debug for FrameHub {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let count = self.subscribers.lock().map(|s| s.len()).unwrap_or(0);
+        f.debug_struct("FrameHub").field("subscribers", &count).finish()
+    }
+}
+
+/// Run `callback` for every frame on a dedicated thread until unsubscribed.
+pub(crate) fn spawn_callback<F>(hub: &Arc<FrameHub>, capacity: usize, mut callback: F) -> std::result::Result<SubscriptionId, PlayerError>
+where
+    F: FnMut(&Frame) + Send + 'static,
+{
+    let (id, queue) = hub.subscribe(capacity);
+    let spawned = std::thread::Builder::new()
+        .name("rtsp-player-frames".into())
+        .spawn(move || {
+            while let Some(frame) = queue.pop(None) {
+                callback(&frame);
+            }
+        });
+
+    match spawned {
+        Ok(_) => Ok(id),
+        Err(err) => {
+            hub.unsubscribe(id);
+            Err(PlayerError::InitError(format!("Failed to start frame thread: {}", err)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_drops_oldest() {
+        let queue = FrameQueue::new(2);
+        for i in 1..=4 {
+            assert!(queue.push(i));
+        }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.pop(Some(Duration::from_millis(10))), Some(4));
+        assert_eq!(queue.pop(Some(Duration::from_millis(10))), None);
+    }
+
+    #[test]
+    fn test_closed_queue_ends_after_draining() {
+        let queue = FrameQueue::new(4);
+        queue.push(1);
+        queue.close();
+        assert!(!queue.push(2));
+        assert_eq!(queue.pop(None), Some(1));
+        assert_eq!(queue.pop(None), None);
+    }
+}
@@ -0,0 +1,435 @@
+use crate::dispatch::BusDispatcher;
+use crate::pipeline::require_element;
+use crate::{GstErrorInfo, PixelFormat, PlayerError, PlayerEvent, RtspPlayer, RtspPlayerConfig, VideoSinkKind};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use gstreamer_video::prelude::VideoOverlayExtManual;
+use std::sync::{Arc, Mutex};
+
+/// Streams a single grid may hold, enough for an 8x8 wall.
+const MAX_TILES: usize = 64;
+
+/// Output geometry and rendering of a [`GridPlayer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridOptions {
+    /// Size of the composited picture, the window scales it.
+    pub width: u32,
+    pub height: u32,
+    /// Tiles per row, `None` picks the smallest square grid that fits.
+    pub columns: Option<u32>,
+    pub video_sink: VideoSinkKind,
+    pub headless: bool,
+    /// Draw each stream's name in the top left corner of its tile.
+    pub labels: bool,
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        GridOptions {
+            width: 1920,
+            height: 1080,
+            columns: None,
+            video_sink: VideoSinkKind::Auto,
+            headless: false,
+            labels: true,
+        }
+    }
+}
+
+impl GridOptions {
+    fn validate(&self, count: usize) -> std::result::Result<(), PlayerError> {
+        let invalid = |msg: &str| Err(PlayerError::ConfigError(msg.into()));
+
+        if count == 0 || count > MAX_TILES {
+            return invalid("A grid needs between 1 and 64 streams");
+        }
+        if self.width == 0 || self.height == 0 {
+            return invalid("Grid size must be greater than zero");
+        }
+        if self.columns == Some(0) {
+            return invalid("A grid needs at least one column");
+        }
+        Ok(())
+    }
+}
+
+/// Position of a tile in the composited picture, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl TileRect {
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Split `width` x `height` into `count` tiles, row by row. The last row may
+/// be incomplete; rounding leftovers go to the last row and column.
+pub fn grid_layout(count: usize, columns: Option<u32>, width: u32, height: u32) -> Vec<TileRect> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let columns = columns
+        .map(|columns| columns as usize)
+        .unwrap_or_else(|| (count as f64).sqrt().ceil() as usize)
+        .clamp(1, count);
+    let rows = count.div_ceil(columns);
+    let (width, height) = (width as i32, height as i32);
+    let edge = |index: usize, parts: usize, total: i32| (index as i64 * total as i64 / parts as i64) as i32;
+
+    (0..count)
+        .map(|index| {
+            let (row, column) = (index / columns, index % columns);
+            let (x, y) = (edge(column, columns, width), edge(row, rows, height));
+            TileRect {
+                x,
+                y,
+                width: edge(column + 1, columns, width) - x,
+                height: edge(row + 1, rows, height) - y,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct Tile {
+    player: RtspPlayer,
+    label: String,
+    pad: gst::Pad,
+}
+
+/// Several streams composited into one picture, a video wall.
+///
+/// Every tile is a full [`RtspPlayer`] decoding into an `appsink`, so each
+/// reconnects on its own while the others keep playing. The frames are fed
+/// into a single `compositor` pipeline:
+///
+/// `appsrc ! videoconvert ! textoverlay ! compositor ! capsfilter ! videoconvert ! sink`
+#[derive(Debug)]
+pub struct GridPlayer {
+    tiles: Vec<Tile>,
+    layout: Vec<TileRect>,
+    options: GridOptions,
+    pipeline: gst::Pipeline,
+    video_sink: VideoSinkKind,
+    maximized: Mutex<Option<usize>>,
+    bus_watch: Mutex<Option<glib::Source>>,
+    dispatcher: Arc<BusDispatcher>,
+}
+
+impl GridPlayer {
+    /// Create a grid from one configuration per tile. The video and audio
+    /// settings of the configurations are replaced, tiles always decode into
+    /// frames and play no audio.
+    pub fn new(streams: Vec<RtspPlayerConfig>, options: GridOptions) -> std::result::Result<Self, PlayerError> {
+        if gst::init().is_err() {
+            return Err(PlayerError::InitError("Failed to initialize GStreamer".into()));
+        }
+        options.validate(streams.len())?;
+        for factory in ["appsrc", "compositor", "videoconvert", "capsfilter", "textoverlay"] {
+            require_element(factory)?;
+        }
+
+        let video_sink = options.video_sink.resolve(options.headless)?;
+        let layout = grid_layout(streams.len(), options.columns, options.width, options.height);
+        let pipeline = gst::Pipeline::with_name("rtsp-grid");
+        let compositor = build_output(&pipeline, &options, &video_sink)?;
+        // One bus thread for the grid and all of its tiles
+        let dispatcher = Arc::new(BusDispatcher::spawn("rtsp-grid-bus")?);
+
+        let mut tiles = Vec::with_capacity(streams.len());
+        for (index, mut config) in streams.into_iter().enumerate() {
+            config.video_sink = VideoSinkKind::App;
+            config.headless = true;
+            config.audio = false;
+            config.frame_format = PixelFormat::Bgra;
+
+            let label = config.display_name();
+            let player = RtspPlayer::with_dispatcher(config, Arc::clone(&dispatcher))?;
+            let (pad, overlay) = add_tile(&pipeline, &compositor, index, &player, options.labels.then_some(label.as_str()))?;
+            set_tile_geometry(&pad, &layout[index], index as u32, true);
+
+            if let Some(overlay) = overlay {
+                let name = label.clone();
+                player.on_event(move |event| {
+                    if let Some(text) = tile_status(&name, event) {
+                        overlay.set_property("text", text);
+                    }
+                });
+            }
+            tiles.push(Tile { player, label, pad });
+        }
+
+        let grid = GridPlayer {
+            tiles,
+            layout,
+            options,
+            pipeline,
+            video_sink,
+            maximized: Mutex::new(None),
+            bus_watch: Mutex::new(None),
+            dispatcher,
+        };
+        grid.watch_bus()?;
+        Ok(grid)
+    }
+
+    /// Start the compositor and every tile.
+    pub fn play(&self) -> std::result::Result<(), PlayerError> {
+        self.pipeline.set_state(gst::State::Playing)?;
+        for tile in &self.tiles {
+            if let Err(err) = tile.player.play() {
+                println!("Tile {} failed to start: {}", tile.label, err);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop(&self) -> std::result::Result<(), PlayerError> {
+        for tile in &self.tiles {
+            let _ = tile.player.stop();
+        }
+        self.pipeline.set_state(gst::State::Null)?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The player behind a tile, for its events, stats, recording or snapshots.
+    pub fn player(&self, index: usize) -> Option<&RtspPlayer> {
+        self.tiles.get(index).map(|tile| &tile.player)
+    }
+
+    pub fn label(&self, index: usize) -> Option<&str> {
+        self.tiles.get(index).map(|tile| tile.label.as_str())
+    }
+
+    pub fn options(&self) -> &GridOptions {
+        &self.options
+    }
+
+    /// Tile positions in the composited picture.
+    pub fn layout(&self) -> &[TileRect] {
+        &self.layout
+    }
+
+    /// The tile shown at `x`, `y` of the composited picture.
+    pub fn tile_at(&self, x: i32, y: i32) -> Option<usize> {
+        if let Some(index) = self.maximized() {
+            return Some(index);
+        }
+        self.layout.iter().position(|rect| rect.contains(x, y))
+    }
+
+    /// Show one tile over the whole picture. The other tiles keep decoding.
+    pub fn maximize(&self, index: usize) -> std::result::Result<(), PlayerError> {
+        if index >= self.tiles.len() {
+            return Err(PlayerError::ConfigError(format!("No tile {}", index)));
+        }
+        let full = TileRect { x: 0, y: 0, width: self.options.width as i32, height: self.options.height as i32 };
+        for (other, tile) in self.tiles.iter().enumerate() {
+            if other == index {
+                set_tile_geometry(&tile.pad, &full, self.tiles.len() as u32, true);
+            } else {
+                set_tile_geometry(&tile.pad, &self.layout[other], other as u32, false);
+            }
+        }
+        *self.maximized.lock().unwrap() = Some(index);
+        Ok(())
+    }
+
+    /// Go back to the grid after [`GridPlayer::maximize`].
+    pub fn restore(&self) {
+        for (index, tile) in self.tiles.iter().enumerate() {
+            set_tile_geometry(&tile.pad, &self.layout[index], index as u32, true);
+        }
+        *self.maximized.lock().unwrap() = None;
+    }
+
+    /// Maximize the tile, or restore the grid if a tile is maximized already.
+    pub fn toggle_maximize(&self, index: usize) -> std::result::Result<(), PlayerError> {
+        if self.maximized().is_some() {
+            self.restore();
+            Ok(())
+        } else {
+            self.maximize(index)
+        }
+    }
+
+    pub fn maximized(&self) -> Option<usize> {
+        *self.maximized.lock().unwrap()
+    }
+
+    /// Render the grid into an existing window, see [`RtspPlayer::set_window_handle`].
+    pub fn set_window_handle(&self, handle: usize) -> std::result::Result<(), PlayerError> {
+        let overlay = self
+            .pipeline
+            .by_name("videosink")
+            .and_then(|sink| sink.dynamic_cast::<gst_video::VideoOverlay>().ok())
+            .ok_or_else(|| PlayerError::SinkError(GstErrorInfo::local(format!("Video sink {} does not support window embedding", self.video_sink))))?;
+        unsafe { overlay.set_window_handle(handle) };
+        Ok(())
+    }
+
+    fn watch_bus(&self) -> std::result::Result<(), PlayerError> {
+        let bus = self.pipeline.bus().ok_or_else(|| PlayerError::InitError("Failed to get pipeline bus".into()))?;
+        let bus_watch = self.dispatcher.watch_bus(&bus, |_, msg| {
+            if let gst::MessageView::Error(err) = msg.view() {
+                let error = PlayerError::from_error_message(err);
+                println!("Grid error [{:?}]: {} ({:?})", error.code(), error, error.gst_info().and_then(|info| info.debug.as_deref()));
+            }
+            glib::ControlFlow::Continue
+        });
+        *self.bus_watch.lock().unwrap() = Some(bus_watch);
+        Ok(())
+    }
+}
+
+impl Drop for GridPlayer {
+    fn drop(&mut self) {
+        if let Some(bus_watch) = self.bus_watch.lock().unwrap().take() {
+            bus_watch.destroy();
+        }
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+/// `compositor ! capsfilter ! videoconvert ! sink`, returns the compositor.
+fn build_output(pipeline: &gst::Pipeline, options: &GridOptions, video_sink: &VideoSinkKind) -> std::result::Result<gst::Element, PlayerError> {
+    let compositor = gst::ElementFactory::make("compositor")
+        .name("compositor")
+        .property_from_str("background", "black")
+        .build()?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst_video::VideoCapsBuilder::new()
+                .width(options.width as i32)
+                .height(options.height as i32)
+                .build(),
+        )
+        .build()?;
+    let convert = gst::ElementFactory::make("videoconvert").build()?;
+    let factory = video_sink.factory_name().expect("resolved sinks have a factory");
+    let sink = gst::ElementFactory::make(factory).name("videosink").build()?;
+
+    pipeline.add_many([&compositor, &capsfilter, &convert, &sink])?;
+    gst::Element::link_many([&compositor, &capsfilter, &convert, &sink])?;
+    Ok(compositor)
+}
+
+/// `appsrc ! videoconvert [! textoverlay]` linked to a new compositor pad,
+/// fed with the player's frames.
+fn add_tile(
+    pipeline: &gst::Pipeline,
+    compositor: &gst::Element,
+    index: usize,
+    player: &RtspPlayer,
+    label: Option<&str>,
+) -> std::result::Result<(gst::Pad, Option<gst::Element>), PlayerError> {
+    let appsrc = gst_app::AppSrc::builder()
+        .name(format!("tile{}", index))
+        .is_live(true)
+        .format(gst::Format::Time)
+        .do_timestamp(true)
+        .build();
+    // Never queue up stale frames behind a slow compositor (GStreamer 1.20+)
+    if appsrc.find_property("leaky-type").is_some() {
+        appsrc.set_property("max-buffers", 2u64);
+        appsrc.set_property_from_str("leaky-type", "downstream");
+    }
+    let convert = gst::ElementFactory::make("videoconvert").build()?;
+    let overlay = match label {
+        Some(label) => Some(
+            gst::ElementFactory::make("textoverlay")
+                .property("text", label)
+                .property("shaded-background", true)
+                .property_from_str("valignment", "top")
+                .property_from_str("halignment", "left")
+                .build()?,
+        ),
+        None => None,
+    };
+
+    let mut chain = vec![appsrc.clone().upcast::<gst::Element>(), convert];
+    chain.extend(overlay.clone());
+    pipeline.add_many(&chain)?;
+    gst::Element::link_many(&chain)?;
+
+    let pad = compositor
+        .request_pad_simple("sink_%u")
+        .ok_or_else(|| PlayerError::InitError("Could not get a compositor pad".into()))?;
+    if pad.find_property("sizing-policy").is_some() {
+        pad.set_property_from_str("sizing-policy", "keep-aspect-ratio");
+    }
+    let tail = chain.last().expect("tile chain is not empty");
+    tail.static_pad("src")
+        .expect("tile chain has a source pad")
+        .link(&pad)
+        .map_err(|err| PlayerError::InitError(format!("Could not link tile {}: {:?}", index, err)))?;
+
+    player.on_frame(move |frame| {
+        let _ = appsrc.push_sample(&frame.to_sample());
+    })?;
+    Ok((pad, overlay))
+}
+
+fn set_tile_geometry(pad: &gst::Pad, rect: &TileRect, zorder: u32, visible: bool) {
+    pad.set_property("xpos", rect.x);
+    pad.set_property("ypos", rect.y);
+    pad.set_property("width", rect.width);
+    pad.set_property("height", rect.height);
+    pad.set_property("zorder", zorder);
+    pad.set_property("alpha", if visible { 1.0f64 } else { 0.0 });
+}
+
+/// Label text for events that change what the tile shows.
+fn tile_status(name: &str, event: &PlayerEvent) -> Option<String> {
+    match event {
+        PlayerEvent::Reconnecting { attempt, .. } => Some(format!("{} - reconnecting ({})", name, attempt)),
+        PlayerEvent::ReconnectGaveUp { .. } => Some(format!("{} - offline", name)),
+        PlayerEvent::AuthenticationFailed { .. } => Some(format!("{} - authentication failed", name)),
+        PlayerEvent::CertificateError { accepted: false, .. } => Some(format!("{} - certificate rejected", name)),
+        PlayerEvent::Stalled { .. } => Some(format!("{} - stalled", name)),
+        PlayerEvent::Reconnected { .. } | PlayerEvent::StreamStarted => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_square_layout() {
+        let layout = grid_layout(4, None, 1920, 1080);
+        assert_eq!(layout[0], TileRect { x: 0, y: 0, width: 960, height: 540 });
+        assert_eq!(layout[3], TileRect { x: 960, y: 540, width: 960, height: 540 });
+        assert!(layout[3].contains(1919, 1079));
+        assert!(!layout[0].contains(960, 0));
+    }
+
+    #[test]
+    fn test_uneven_layout_covers_picture() {
+        let layout = grid_layout(5, None, 1000, 700);
+        // 3 columns, 2 rows, last row incomplete
+        assert_eq!(layout.len(), 5);
+        assert_eq!(layout[2].x + layout[2].width, 1000);
+        assert_eq!(layout[4].y + layout[4].height, 700);
+        assert_eq!(grid_layout(3, Some(1), 100, 300)[2], TileRect { x: 0, y: 200, width: 100, height: 100 });
+        assert!(grid_layout(0, None, 100, 100).is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{GridPlayer, PlayerError};
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::Graphics::Gdi::UpdateWindow,
+    Win32::System::LibraryLoader::GetModuleHandleA,
+    Win32::UI::WindowsAndMessaging::*,
+};
+
+const GRID_CLASS_NAME: PCSTR = PCSTR(b"RTSPPlayerGridWindowClass\0".as_ptr());
+
+/// Win32 window showing a [`GridPlayer`]. Double-clicking a tile maximizes
+/// it, double-clicking again returns to the grid.
+///
+/// Unlike [`crate::Win32Gui`] the window procedure is built in, and the
+/// window class is shared, so any number of grid windows can be opened.
+#[derive(Debug)]
+pub struct Win32GridWindow {
+    grid: GridPlayer,
+}
+
+impl Win32GridWindow {
+    pub fn new(grid: GridPlayer) -> Self {
+        Win32GridWindow { grid }
+    }
+
+    pub fn grid(&self) -> &GridPlayer {
+        &self.grid
+    }
+
+    /// Open the window and render the grid into it. `self` must stay at the
+    /// same address until the window is destroyed.
+    pub fn create_window(&self, title: &str) -> std::result::Result<HWND, PlayerError> {
+        let instance = unsafe { GetModuleHandleA(None)? };
+        let hinstance = HINSTANCE(instance.0);
+
+        let wc = WNDCLASSA {
+            style: CS_HREDRAW | CS_VREDRAW | CS_DBLCLKS,
+            lpfnWndProc: Some(grid_window_proc),
+            hInstance: hinstance,
+            lpszClassName: GRID_CLASS_NAME,
+            hCursor: unsafe { LoadCursorW(None, IDC_ARROW)? },
+            ..Default::default()
+        };
+        if unsafe { RegisterClassA(&wc) } == 0 && unsafe { GetLastError() } != ERROR_CLASS_ALREADY_EXISTS {
+            return Err(PlayerError::WindowsError("Failed to register grid window class".into()));
+        }
+
+        let title = format!("{}\0", title);
+        let window = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                GRID_CLASS_NAME,
+                PCSTR(title.as_ptr()),
+                WS_OVERLAPPEDWINDOW | WS_VISIBLE,
+                CW_USEDEFAULT, CW_USEDEFAULT, 1280, 720,
+                None,
+                None,
+                Some(hinstance),
+                Some(self as *const Self as *const _),
+            )
+        }?;
+
+        unsafe {
+            let _ = ShowWindow(window, SW_SHOW);
+            let _ = UpdateWindow(window);
+        }
+        self.grid.set_window_handle(window.0 as usize)?;
+        Ok(window)
+    }
+
+    fn handle_window_message(&self, hwnd: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        match message {
+            WM_LBUTTONDBLCLK => {
+                let x = (lparam.0 & 0xffff) as i16 as i32;
+                let y = ((lparam.0 >> 16) & 0xffff) as i16 as i32;
+                if let Some(index) = self.tile_at_client(hwnd, x, y)
+                    && let Err(err) = self.grid.toggle_maximize(index)
+                {
+                    println!("Failed to maximize tile {}: {}", index, err);
+                }
+                LRESULT(0)
+            }
+            WM_DESTROY => {
+                let _ = self.grid.stop();
+                unsafe { PostQuitMessage(0) };
+                LRESULT(0)
+            }
+            _ => unsafe { DefWindowProcA(hwnd, message, wparam, lparam) },
+        }
+    }
+
+    /// Map a point of the client area to a tile of the scaled grid picture.
+    fn tile_at_client(&self, hwnd: HWND, x: i32, y: i32) -> Option<usize> {
+        let mut client = RECT::default();
+        unsafe { GetClientRect(hwnd, &mut client) }.ok()?;
+        let (client_width, client_height) = (client.right - client.left, client.bottom - client.top);
+        if client_width <= 0 || client_height <= 0 {
+            return None;
+        }
+        let options = self.grid.options();
+        let grid_x = (x as i64 * options.width as i64 / client_width as i64) as i32;
+        let grid_y = (y as i64 * options.height as i64 / client_height as i64) as i32;
+        self.grid.tile_at(grid_x, grid_y)
+    }
+}
+
+extern "system" fn grid_window_proc(hwnd: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if message == WM_CREATE {
+        let create_struct = unsafe { &*(lparam.0 as *const CREATESTRUCTA) };
+        unsafe { SetWindowLongPtrA(hwnd, GWLP_USERDATA, create_struct.lpCreateParams as isize) };
+        return LRESULT(0);
+    }
+
+    let window = unsafe { GetWindowLongPtrA(hwnd, GWLP_USERDATA) } as *const Win32GridWindow;
+    if window.is_null() {
+        return unsafe { DefWindowProcA(hwnd, message, wparam, lparam) };
+    }
+    unsafe { &*window }.handle_window_message(hwnd, message, wparam, lparam)
+}
@@ -0,0 +1,504 @@
+use crate::{AudioInfo, EventReceiver, PlayerError, PlayerEvent, RtspPlayer, VideoInfo};
+use std::os::raw::c_void;
+use std::sync::Mutex;
+use windows::{
+    core::*,
+    Win32::Foundation::*,
+    Win32::UI::Controls::*,
+    Win32::UI::WindowsAndMessaging::*,
+    Win32::Graphics::Gdi::*,
+    Win32::System::LibraryLoader::GetModuleHandleA,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct GuiControls {
+    window: Option<HWND>,
+    video_window: Option<HWND>,
+    play_button: Option<HWND>,
+    pause_button: Option<HWND>,
+    stop_button: Option<HWND>,
+    seekbar: Option<HWND>,
+    status_text: Option<HWND>,
+    overlay_text: Option<HWND>,
+}
+
+const ID_PLAY_BUTTON: u16 = 101;
+const ID_PAUSE_BUTTON: u16 = 102;
+const ID_STOP_BUTTON: u16 = 103;
+const ID_SEEKBAR: u16 = 104;
+const ID_STATUS_TEXT: u16 = 105;
+const ID_VIDEO_WINDOW: u16 = 106;
+
+const TIMER_PLAYER_MESSAGES: usize = 1;
+const TIMER_POSITION: usize = 2;
+
+/// Win32 window with playback controls around an [`RtspPlayer`].
+///
+/// The window procedure passed to [`Win32Gui::create_gui`] receives a pointer to
+/// this struct in `CREATESTRUCTA::lpCreateParams` and should forward messages to
+/// [`Win32Gui::handle_window_message`].
+#[derive(Debug)]
+pub struct Win32Gui {
+    player: RtspPlayer,
+    events: EventReceiver,
+    controls: Mutex<Option<GuiControls>>,
+}
+
+impl Win32Gui {
+    pub fn new(player: RtspPlayer) -> Self {
+        let events = player.subscribe();
+        Win32Gui {
+            player,
+            events,
+            controls: Mutex::new(None),
+        }
+    }
+
+    pub fn player(&self) -> &RtspPlayer {
+        &self.player
+    }
+
+    pub fn create_gui(&self, window_proc: WNDPROC) -> std::result::Result<(), PlayerError> {
+        let instance = unsafe { GetModuleHandleA(None)? };
+        
+        // Register window class
+        let class_name = PCSTR(b"RTSPPlayerWindowClass\0".as_ptr());
+        // let hbrBackground = HBRUSH(COLOR_WINDOW.0);
+        let hInstance = HINSTANCE(instance.0);
+        
+        let wc = WNDCLASSA {
+            style: CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: window_proc,
+            hInstance,
+            lpszClassName: class_name,
+            hCursor: unsafe { LoadCursorW(None, IDC_ARROW)? },
+            // hbrBackground,
+            ..Default::default()
+        };
+        
+        if unsafe { RegisterClassA(&wc) } == 0 {
+            return Err(PlayerError::WindowsError("Failed to register window class".into()));
+        }
+        
+        // Store self pointer for the window procedure to access
+        let player_ptr = Box::into_raw(Box::new(self as *const _));
+        
+        // Create main window
+        let window = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                PCSTR(b"RTSP Player\0".as_ptr()),
+                WS_OVERLAPPEDWINDOW | WS_VISIBLE,
+                CW_USEDEFAULT, CW_USEDEFAULT, 800, 600,
+                None,
+                None,
+                Some(hInstance),
+                Some(player_ptr as *const _),
+            )
+        }?;
+        
+        if window.0.is_null() {
+            return Err(PlayerError::WindowsError("Failed to create window".into()));
+        }
+
+        // let hwndparent = HWND(window.0);
+        // let hmenu = HMENU(ID_VIDEO_WINDOW as isize);
+        // let hmenu = unsafe {CreateMenu()}?;
+        // Menu
+        
+        // Create video window
+        let video_window = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"STATIC\0".as_ptr()),
+                PCSTR(b"\0".as_ptr()),
+                WS_CHILD | WS_VISIBLE | WS_BORDER,
+                0, 0, 800, 500,
+                Some(window),
+                Some(HMENU(ID_VIDEO_WINDOW as *mut c_void)),
+                Some(hInstance),
+                None,
+            )
+        }?;
+
+        const BS_DEFPUSHBUTTON: WINDOW_STYLE = WINDOW_STYLE(windows::Win32::UI::WindowsAndMessaging::BS_DEFPUSHBUTTON as u32);
+        
+        // Create control buttons
+        let play_button = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"BUTTON\0".as_ptr()),
+                PCSTR(b"Play\0".as_ptr()),
+                WS_TABSTOP | WS_VISIBLE | WS_CHILD | BS_DEFPUSHBUTTON,
+                10, 510, 100, 30,
+                Some(window),
+                None, //Some(HMENU(ID_PLAY_BUTTON as isize)),
+                Some(hInstance),
+                None,
+            )
+        }?;
+        
+        let pause_button = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"BUTTON\0".as_ptr()),
+                PCSTR(b"Pause\0".as_ptr()),
+                WS_TABSTOP | WS_VISIBLE | WS_CHILD | BS_DEFPUSHBUTTON,
+                120, 510, 100, 30,
+                Some(window),
+                None, //HMENU(ID_PAUSE_BUTTON as isize),
+                Some(hInstance),
+                None,
+            )
+        }?;
+        
+        let stop_button = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"BUTTON\0".as_ptr()),
+                PCSTR(b"Stop\0".as_ptr()),
+                WS_TABSTOP | WS_VISIBLE | WS_CHILD | BS_DEFPUSHBUTTON,
+                230, 510, 100, 30,
+                Some(window),
+                None, //HMENU(ID_STOP_BUTTON as isize),
+                Some(hInstance),
+                None,
+            )
+        }?;
+        
+        // Create seekbar (trackbar control)
+        let seekbar = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"msctls_trackbar32\0".as_ptr()),
+                PCSTR(b"\0".as_ptr()),
+                WS_CHILD | WS_VISIBLE,// | TBS_HORZ,
+                340, 510, 300, 30,
+                Some(window),
+                None, //HMENU(ID_SEEKBAR as isize),
+                Some(hInstance),
+                None,
+            )
+        }?;
+        
+        // Initialize seekbar range
+        unsafe {
+            SendMessageA(seekbar, TBM_SETRANGE, WPARAM(0), LPARAM(1000));
+        }
+        
+        // Create status text
+        let status_text = unsafe {
+            CreateWindowExA(
+                WINDOW_EX_STYLE::default(),
+                PCSTR(b"STATIC\0".as_ptr()),
+                PCSTR(b"Ready\0".as_ptr()),
+                WS_CHILD | WS_VISIBLE,// | SS_LEFT,
+                10, 550, 780, 20,
+                Some(window),
+                None, //HMENU(ID_STATUS_TEXT as isize),
+                Some(hInstance),
+                None,
+            )
+        }?;
+
+        let window = Some(window);
+        let video_window = Some(video_window);
+        let play_button = Some(play_button);
+        let pause_button = Some(pause_button);
+        let stop_button = Some(stop_button);
+        let seekbar = Some(seekbar);
+        let status_text = Some(status_text);
+        let overlay_text = None;
+        
+        // Store controls
+        *self.controls.lock().unwrap() = Some(GuiControls {
+            window,
+            video_window,
+            play_button,
+            pause_button,
+            stop_button,
+            seekbar,
+            status_text,
+            overlay_text,
+        });
+        
+        // Make the window visible
+        unsafe {
+            check_win_err()?;
+            let r = ShowWindow(window.unwrap(), SW_SHOW);
+            println!("ShowWindow result: {}", r.0);
+            check_win_err()?;
+            let r = UpdateWindow(window.unwrap());
+            println!("UpdateWindow result: {}", r.0);
+            check_win_err()?;
+        }
+        
+        // Render the video into our child window
+        if let Some(video_window) = video_window {
+            self.player.set_window_handle(video_window.0 as usize)?;
+        }
+
+        // Poll the playback engine for messages and position updates
+        unsafe {
+            SetTimer(window, TIMER_PLAYER_MESSAGES, 500, None);
+            SetTimer(window, TIMER_POSITION, 500, None);
+        }
+        
+        Ok(())
+    }
+
+    pub fn handle_window_message(&self, hwnd: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        fn LOWORD(l: u32) -> u16 {
+            (l & 0xffff) as u16
+        }
+        fn HIWORD(l: u32) -> u16 {
+            ((l >> 16) & 0xffff) as u16
+        }
+        match message {
+            WM_COMMAND => {
+                let control_id = LOWORD(wparam.0 as u32);
+                match control_id {
+                    ID_PLAY_BUTTON => {
+                        let _ = self.player.resume();
+                        self.set_status_text("Playing");
+                        LRESULT(0)
+                    },
+                    ID_PAUSE_BUTTON => {
+                        let _ = self.player.pause();
+                        self.set_status_text("Paused");
+                        LRESULT(0)
+                    },
+                    ID_STOP_BUTTON => {
+                        let _ = self.player.stop();
+                        self.set_status_text("Stopped");
+                        LRESULT(0)
+                    },
+                    _ => unsafe { DefWindowProcA(hwnd, message, wparam, lparam) }
+                }
+            },
+            WM_HSCROLL => {
+                if let Some(controls) = &*self.controls.lock().unwrap() {
+                    if let Some(seekbar) = controls.seekbar {
+                        if lparam.0 as isize == seekbar.0 as isize {
+                            let notify_code = LOWORD(wparam.0 as u32);
+                            match notify_code as u32 {
+                                TB_THUMBPOSITION | TB_THUMBTRACK => {
+                                    let position = HIWORD(wparam.0 as u32) as f64 / 1000.0;
+                                    let _ = self.player.seek(position);
+                                },
+                                TB_ENDTRACK => {
+                                    controls.seekbar.and_then(|x| {
+                                        Some(unsafe { SendMessageA(x, TBM_GETTICPOS, WPARAM(0), LPARAM(0)).0 })
+                                    }).map(|pos| {
+                                        let position = pos as f64 / 1000.0;
+                                        let _ = self.player.seek(position);
+                                    }).unwrap_or_default();
+                                    // Get the current position from the trackbar
+                                    // let position = unsafe { 
+                                    //     SendMessageA(controls.seekbar, TBM_GETTICPOS, WPARAM(0), LPARAM(0)).0
+                                    // } as f64 / 1000.0;
+                                    // let _ = self.player.seek(position);
+                                },
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                LRESULT(0)
+            },
+            WM_TIMER => {
+                match wparam.0 {
+                    TIMER_PLAYER_MESSAGES => {
+                        // Process messages from the GStreamer bus thread
+                        self.process_player_messages();
+                    },
+                    TIMER_POSITION => {
+                        // Update position slider
+                        if let Some((pos_secs, dur_secs)) = self.player.update_position() {
+                            if dur_secs > 0 && dur_secs > pos_secs {
+                                if let Some(controls) = &*self.controls.lock().unwrap() {
+                                    if let Some(seekbar) = controls.seekbar {
+                                        let slider_value = ((pos_secs as f64 / dur_secs as f64) * 1000.0) as i32;
+                                        unsafe {
+                                            SendMessageA(
+                                                seekbar, 
+                                                TBM_SETPOS,
+                                                WPARAM(1), // TRUE to redraw
+                                                LPARAM(slider_value as isize)
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+                LRESULT(0)
+            },
+            // ... other message handlers remain the same
+            WM_SIZE => {
+                // Resize video window when main window is resized
+                if let Some(controls) = &*self.controls.lock().unwrap() {
+                    let width = LOWORD(lparam.0 as u32) as i32;
+                    let height = HIWORD(lparam.0 as u32) as i32;
+                    
+                    // Resize video area
+                    unsafe {
+                        controls.video_window.and_then(|video_window| {
+                            Some(MoveWindow(
+                                video_window,
+                                0, 0,
+                                width,
+                                height - 100, // Leave space for controls
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+                        
+                        // Reposition controls
+                        let control_y = height - 90;
+                        
+                        controls.play_button.and_then(|play_button| {
+                            Some(MoveWindow(
+                                play_button,
+                                10, control_y,
+                                100, 30,
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+
+                        controls.pause_button.and_then(|pause_button| {
+                            Some(MoveWindow(
+                                pause_button,
+                                120, control_y,
+                                100, 30,
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+                        
+                        controls.stop_button.and_then(|stop_button| {
+                            Some(MoveWindow(
+                                stop_button,
+                                230, control_y,
+                                100, 30,
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+
+                        controls.seekbar.and_then(|seekbar| {
+                            Some(MoveWindow(
+                                seekbar,
+                                340, control_y,
+                                width - 350, 30,
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+                        
+                        controls.status_text.and_then(|status_text| {
+                            Some(MoveWindow(
+                                status_text,
+                                10, control_y + 40,
+                                width - 20, 20,
+                                true
+                            ))
+                        }).unwrap_or(Ok(()));
+                    }
+                }
+                LRESULT(0)
+            },
+            WM_DESTROY => {
+                // Stop playback and quit
+                let _ = self.player.stop();
+                unsafe { PostQuitMessage(0) };
+                LRESULT(0)
+            },
+            _ => unsafe { DefWindowProcA(hwnd, message, wparam, lparam) }
+        }
+    }
+
+    // Drain the events posted by the playback engine
+    fn process_player_messages(&self) {
+        // Try to receive all pending events without blocking
+        while let Some(event) = self.events.try_recv() {
+            match event {
+                PlayerEvent::EndOfStream => self.set_status_text("End of stream"),
+                PlayerEvent::Error(error) => {
+                    let text = format!("Error: {}", error);
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::AuthenticationFailed { .. } => self.set_status_text("Authentication failed, check username and password"),
+                PlayerEvent::CertificateError { accepted: false, .. } => self.set_status_text("Server certificate rejected"),
+                PlayerEvent::CertificateError { accepted: true, .. } => self.set_status_text("Server certificate accepted despite errors"),
+                PlayerEvent::StreamStarted => self.set_status_text("Stream started"),
+                PlayerEvent::Buffering(percent) => {
+                    let text = format!("Buffering... {}%", percent);
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::StateChanged { current, .. } => self.set_status_text(current.to_string()),
+                PlayerEvent::VideoInfo(VideoInfo { width, height, framerate, codec, .. }) => {
+                    // Update video information in UI
+                    let text = format!("{}x{} @ {:.2} fps ({})", width, height, framerate, codec);
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::AudioInfo(AudioInfo { codec, sample_rate, channels }) => {
+                    let text = format!("Audio: {} {} Hz, {} ch", codec, sample_rate, channels);
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::Reconnecting { attempt, max_attempts, delay, .. } => {
+                    let text = match max_attempts {
+                        Some(max) => format!("Reconnecting in {}s ({}/{})...", delay.as_secs(), attempt, max),
+                        None => format!("Reconnecting in {}s ({})...", delay.as_secs(), attempt),
+                    };
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::Reconnected { .. } => self.set_status_text("Reconnected"),
+                PlayerEvent::Stalled { idle } => {
+                    let text = format!("No video for {}s", idle.as_secs());
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::TransportFallback { to, .. } => {
+                    let text = format!("No UDP data, switching to {}", to);
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::ReconnectGaveUp { .. } => self.set_status_text("Connection failed"),
+                PlayerEvent::RecordingStarted { path } => {
+                    let text = format!("Recording to {}", path.display());
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::RecordingStopped { path } => {
+                    let text = format!("Saved {}", path.display());
+                    self.set_status_text(text.as_str());
+                },
+                PlayerEvent::SegmentClosed { .. } => {
+                    // Segments rotate silently
+                },
+                PlayerEvent::Stats(_) => {
+                    // Position is handled by the position timer
+                },
+            }
+        }
+    }
+
+    fn set_status_text<S: AsRef<str>>(&self, text: S) {
+        let text = format!("{}\0", text.as_ref());
+        let text = text.as_str();
+        // Update status text in the GUI
+        if let Some(controls) = &*self.controls.lock().unwrap() {
+            if let Some(status_text) = controls.status_text {
+                // let text = CString::new(text).unwrap();
+                unsafe {
+                    SetWindowTextA(status_text, PCSTR(text.as_ptr()))
+                }.expect("Failed to set status text");
+            }
+        }
+    }
+}
+
+fn check_win_err() -> std::result::Result<(), PlayerError> {
+    let last_error = unsafe { GetLastError() };
+    if last_error != ERROR_SUCCESS {
+        return Err(PlayerError::WindowsError(format!("0x{:08x}", last_error.0)));
+    }
+    Ok(())
+}
@@ -0,0 +1,271 @@
+use crate::dispatch::BusDispatcher;
+use crate::events::SubscriptionId;
+use crate::player::Shared;
+use crate::{PlayerError, PlayerEvent, RtspPlayer, RtspPlayerConfig};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, Weak};
+
+/// An event of one of the manager's streams.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEvent {
+    /// The id the stream was added under.
+    pub stream: String,
+    pub event: PlayerEvent,
+}
+
+type StreamEventCallback = Arc<dyn Fn(&StreamEvent) + Send + Sync>;
+
+#[derive(Default)]
+struct Listeners {
+    next_id: SubscriptionId,
+    channels: Vec<Sender<StreamEvent>>,
+    callbacks: Vec<(SubscriptionId, StreamEventCallback)>,
+}
+
+impl Listeners {
+    fn emit(listeners: &Mutex<Listeners>, event: StreamEvent) {
+        let callbacks: Vec<StreamEventCallback> = {
+            let mut listeners = listeners.lock().unwrap();
+            listeners.channels.retain(|sender| sender.send(event.clone()).is_ok());
+            listeners.callbacks.iter().map(|(_, callback)| Arc::clone(callback)).collect()
+        };
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+}
+
+/// The decoder cap of a manager, checked by every player it created so
+/// that calling [`RtspPlayer::play`] directly cannot get around it.
+#[derive(Debug)]
+pub(crate) struct DecoderLimit {
+    max: usize,
+    players: Mutex<Vec<Weak<Shared>>>,
+}
+
+impl DecoderLimit {
+    fn new(max: usize) -> Self {
+        DecoderLimit { max, players: Mutex::new(Vec::new()) }
+    }
+
+    pub(crate) fn register(&self, player: &Arc<Shared>) {
+        self.players.lock().unwrap().push(Arc::downgrade(player));
+    }
+
+    /// Run `start` for `player` unless the other players use up every decoder.
+    /// A player holds its decoder from its first start until it is stopped
+    /// or dropped, also while paused.
+    /// The check and the start happen under one lock, so two streams cannot
+    /// both take the last decoder.
+    pub(crate) fn start<F>(&self, player: &Shared, start: F) -> std::result::Result<(), PlayerError>
+    where
+        F: FnOnce() -> std::result::Result<(), PlayerError>,
+    {
+        let mut players = self.players.lock().unwrap();
+        players.retain(|other| other.strong_count() > 0);
+        if !player.holds_decoder() {
+            let active = players.iter().filter_map(Weak::upgrade).filter(|other| other.holds_decoder()).count();
+            if active >= self.max {
+                return Err(PlayerError::LimitReached(format!(
+                    "{} of {} decoders in use, cannot start '{}'",
+                    active,
+                    self.max,
+                    player.config.display_name()
+                )));
+            }
+        }
+        start()
+    }
+}
+
+/// Runs many [`RtspPlayer`]s in one process, e.g. in a recording or
+/// analytics service.
+///
+/// All players share one bus thread instead of one each, their events are
+/// merged into a single feed tagged with the stream id, and the number of
+/// streams decoding at the same time can be capped.
+pub struct PlayerManager {
+    players: Mutex<BTreeMap<String, Arc<RtspPlayer>>>,
+    decoder_limit: Option<Arc<DecoderLimit>>,
+    listeners: Arc<Mutex<Listeners>>,
+    dispatcher: Arc<BusDispatcher>,
+}
+
+impl PlayerManager {
+    pub fn new() -> std::result::Result<Self, PlayerError> {
+        Ok(PlayerManager {
+            players: Mutex::new(BTreeMap::new()),
+            decoder_limit: None,
+            listeners: Arc::new(Mutex::new(Listeners::default())),
+            dispatcher: Arc::new(BusDispatcher::spawn("rtsp-manager-bus")?),
+        })
+    }
+
+    /// A manager that lets at most `max_decoders` streams play at once.
+    pub fn with_max_decoders(max_decoders: usize) -> std::result::Result<Self, PlayerError> {
+        if max_decoders == 0 {
+            return Err(PlayerError::ConfigError("At least one decoder must be allowed".into()));
+        }
+        let mut manager = Self::new()?;
+        manager.decoder_limit = Some(Arc::new(DecoderLimit::new(max_decoders)));
+        Ok(manager)
+    }
+
+    pub fn max_decoders(&self) -> Option<usize> {
+        self.decoder_limit.as_ref().map(|limit| limit.max)
+    }
+
+    /// Create a player for `config` under `id`. It stays stopped until
+    /// [`PlayerManager::play`] is called. The decoder limit also holds when
+    /// the returned player is started directly.
+    pub fn add(&self, id: &str, config: RtspPlayerConfig) -> std::result::Result<Arc<RtspPlayer>, PlayerError> {
+        let mut players = self.players.lock().unwrap();
+        if players.contains_key(id) {
+            return Err(PlayerError::ConfigError(format!("Stream id '{}' is already in use", id)));
+        }
+
+        let mut player = RtspPlayer::with_dispatcher(config, Arc::clone(&self.dispatcher))?;
+        if let Some(limit) = &self.decoder_limit {
+            player = player.limit_decoders(Arc::clone(limit));
+        }
+        let player = Arc::new(player);
+        let listeners = Arc::clone(&self.listeners);
+        let stream = id.to_string();
+        player.on_event(move |event| {
+            Listeners::emit(&listeners, StreamEvent { stream: stream.clone(), event: event.clone() });
+        });
+
+        players.insert(id.to_string(), Arc::clone(&player));
+        println!("Added stream {}", id);
+        Ok(player)
+    }
+
+    /// Stop and remove a stream. Returns `false` for unknown ids.
+    pub fn remove(&self, id: &str) -> bool {
+        let Some(player) = self.players.lock().unwrap().remove(id) else { return false };
+        if let Err(err) = player.stop() {
+            println!("Stopping stream {} failed: {}", id, err);
+        }
+        println!("Removed stream {}", id);
+        true
+    }
+
+    pub fn player(&self, id: &str) -> Option<Arc<RtspPlayer>> {
+        self.players.lock().unwrap().get(id).cloned()
+    }
+
+    /// Ids of all streams, sorted.
+    pub fn ids(&self) -> Vec<String> {
+        self.players.lock().unwrap().keys().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.lock().unwrap().is_empty()
+    }
+
+    /// Streams holding a decoder: playing, paused or reconnecting.
+    pub fn active_decoders(&self) -> usize {
+        self.players.lock().unwrap().values().filter(|player| player.holds_decoder()).count()
+    }
+
+    /// Start a stream. Fails with [`PlayerError::LimitReached`] when the
+    /// decoder limit is already used up by other streams.
+    pub fn play(&self, id: &str) -> std::result::Result<(), PlayerError> {
+        let player = self.player(id).ok_or_else(|| unknown_stream(id))?;
+        if player.is_playing() {
+            return Ok(());
+        }
+        player.play()
+    }
+
+    pub fn stop(&self, id: &str) -> std::result::Result<(), PlayerError> {
+        let player = self.player(id).ok_or_else(|| unknown_stream(id))?;
+        player.stop()
+    }
+
+    pub fn stop_all(&self) {
+        let players: Vec<(String, Arc<RtspPlayer>)> = self.players
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, player)| (id.clone(), Arc::clone(player)))
+            .collect();
+        for (id, player) in players {
+            if let Err(err) = player.stop() {
+                println!("Stopping stream {} failed: {}", id, err);
+            }
+        }
+    }
+
+    /// Receive the events of every stream, tagged with the stream id.
+    pub fn subscribe(&self) -> Receiver<StreamEvent> {
+        let (sender, receiver) = channel();
+        self.listeners.lock().unwrap().channels.push(sender);
+        receiver
+    }
+
+    /// Call `callback` for the events of every stream. It runs on the shared
+    /// bus thread and must return quickly, or all streams' events are delayed.
+    pub fn on_event<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&StreamEvent) + Send + Sync + 'static,
+    {
+        let mut listeners = self.listeners.lock().unwrap();
+        listeners.next_id += 1;
+        let id = listeners.next_id;
+        listeners.callbacks.push((id, Arc::new(callback)));
+        id
+    }
+
+    pub fn remove_event_callback(&self, id: SubscriptionId) -> bool {
+        let mut listeners = self.listeners.lock().unwrap();
+        let before = listeners.callbacks.len();
+        listeners.callbacks.retain(|(entry_id, _)| *entry_id != id);
+        listeners.callbacks.len() != before
+    }
+}
+
+impl Drop for PlayerManager {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+impl std::fmt::Debug for PlayerManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PlayerManager")
+            .field("streams", &self.ids())
+            .field("max_decoders", &self.max_decoders())
+            .finish()
+    }
+}
+
+fn unknown_stream(id: &str) -> PlayerError {
+    PlayerError::ConfigError(format!("No stream with id '{}'", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_tagged_and_fanned_out() {
+        let listeners = Arc::new(Mutex::new(Listeners::default()));
+        let (sender, receiver) = channel();
+        listeners.lock().unwrap().channels.push(sender);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_by_callback = Arc::clone(&seen);
+        listeners.lock().unwrap().callbacks.push((1, Arc::new(move |event: &StreamEvent| {
+            seen_by_callback.lock().unwrap().push(event.stream.clone());
+        })));
+
+        Listeners::emit(&listeners, StreamEvent { stream: "cam1".into(), event: PlayerEvent::StreamStarted });
+        assert_eq!(receiver.recv().unwrap().stream, "cam1");
+        assert_eq!(*seen.lock().unwrap(), vec!["cam1".to_string()]);
+    }
+}
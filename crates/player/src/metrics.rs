@@ -0,0 +1,264 @@
+use crate::player::Shared;
+use crate::{PlayerError, PlayerState, StreamStats};
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How long a scraper may take to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Every live player in the process, in creation order.
+static PLAYERS: Mutex<Vec<Weak<Shared>>> = Mutex::new(Vec::new());
+
+/// A player's metrics at the time of a scrape.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PlayerMetrics {
+    /// Configured name, or the URL without credentials.
+    pub(crate) stream: String,
+    /// URL without credentials.
+    pub(crate) url: String,
+    pub(crate) state: Option<PlayerState>,
+    pub(crate) stats: StreamStats,
+}
+
+pub(crate) fn register(shared: &Arc<Shared>) {
+    let mut players = PLAYERS.lock().unwrap();
+    players.retain(|player| player.strong_count() > 0);
+    players.push(Arc::downgrade(shared));
+}
+
+/// Prometheus text exposition of every `RtspPlayer` in the process.
+///
+/// For applications that already run an HTTP server; [`serve_metrics`]
+/// serves the same text on its own port.
+pub fn metrics_text() -> String {
+    let players: Vec<Arc<Shared>> = PLAYERS.lock().unwrap().iter().filter_map(Weak::upgrade).collect();
+    let metrics: Vec<PlayerMetrics> = players.iter().map(|player| player.metrics()).collect();
+    render(&metrics)
+}
+
+struct Family {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    value: fn(&PlayerMetrics) -> f64,
+}
+
+const FAMILIES: [Family; 10] = [
+    Family {
+        name: "rtsp_player_reconnects_total",
+        kind: "counter",
+        help: "Reconnection attempts since the player was created.",
+        value: |m| m.stats.reconnects_total as f64,
+    },
+    Family {
+        name: "rtsp_player_reconnect_attempts",
+        kind: "gauge",
+        help: "Reconnection attempts since the stream last reached playing.",
+        value: |m| m.stats.reconnect_attempts as f64,
+    },
+    Family {
+        name: "rtsp_player_stalls_total",
+        kind: "counter",
+        help: "Times no video was decoded for the stall timeout.",
+        value: |m| m.stats.stalls as f64,
+    },
+    Family {
+        name: "rtsp_player_bitrate_bits_per_second",
+        kind: "gauge",
+        help: "Video bitrate received from the network.",
+        value: |m| m.stats.bitrate_bps as f64,
+    },
+    Family {
+        name: "rtsp_player_fps",
+        kind: "gauge",
+        help: "Frames decoded per second.",
+        value: |m| m.stats.fps,
+    },
+    Family {
+        name: "rtsp_player_frames_decoded_total",
+        kind: "counter",
+        help: "Frames decoded since the player was created.",
+        value: |m| m.stats.frames_decoded as f64,
+    },
+    Family {
+        name: "rtsp_player_frames_dropped_total",
+        kind: "counter",
+        help: "Frames the video sink dropped for being late.",
+        value: |m| m.stats.frames_dropped as f64,
+    },
+    Family {
+        name: "rtsp_player_qos_dropped_total",
+        kind: "counter",
+        help: "Buffers dropped according to QoS messages of the decoder and sinks.",
+        value: |m| m.stats.qos_dropped as f64,
+    },
+    Family {
+        name: "rtsp_player_packets_lost_total",
+        kind: "counter",
+        help: "RTP packets the jitterbuffer considered lost.",
+        value: |m| m.stats.jitterbuffer.lost as f64,
+    },
+    Family {
+        name: "rtsp_player_uptime_seconds",
+        kind: "gauge",
+        help: "Seconds since the player was created.",
+        value: |m| m.stats.uptime.as_secs_f64(),
+    },
+];
+
+const STATES: [(PlayerState, &str); 4] = [
+    (PlayerState::Stopped, "stopped"),
+    (PlayerState::Ready, "ready"),
+    (PlayerState::Paused, "paused"),
+    (PlayerState::Playing, "playing"),
+];
+
+fn render(players: &[PlayerMetrics]) -> String {
+    let mut text = String::new();
+
+    let _ = writeln!(text, "# HELP rtsp_player_state Pipeline state, 1 for the current one.");
+    let _ = writeln!(text, "# TYPE rtsp_player_state gauge");
+    for player in players {
+        for (state, label) in STATES {
+            let value = u8::from(player.state == Some(state));
+            let _ = writeln!(text, "rtsp_player_state{{{},state=\"{}\"}} {}", labels(player), label, value);
+        }
+    }
+
+    for family in &FAMILIES {
+        let _ = writeln!(text, "# HELP {} {}", family.name, family.help);
+        let _ = writeln!(text, "# TYPE {} {}", family.name, family.kind);
+        for player in players {
+            let _ = writeln!(text, "{}{{{}}} {}", family.name, labels(player), (family.value)(player));
+        }
+    }
+    text
+}
+
+fn labels(player: &PlayerMetrics) -> String {
+    format!("stream=\"{}\",url=\"{}\"", escape(&player.stream), escape(&player.url))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// HTTP endpoint serving [`metrics_text`] at `/metrics`. Stops when dropped.
+#[derive(Debug)]
+pub struct MetricsServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// The bound address, useful when listening on port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn shutdown(&mut self) {
+        let Some(thread) = self.thread.take() else { return };
+        self.stop.store(true, Ordering::SeqCst);
+        // Wake the blocking accept
+        let mut wake = self.addr;
+        if wake.ip().is_unspecified() {
+            wake.set_ip(if wake.is_ipv4() { Ipv4Addr::LOCALHOST.into() } else { Ipv6Addr::LOCALHOST.into() });
+        }
+        let _ = TcpStream::connect_timeout(&wake, Duration::from_secs(1));
+        let _ = thread.join();
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Serve Prometheus metrics of every player in the process on `addr`,
+/// e.g. `127.0.0.1:9184`.
+pub fn serve_metrics<A: ToSocketAddrs>(addr: A) -> std::result::Result<MetricsServer, PlayerError> {
+    let listener = TcpListener::bind(addr)
+        .map_err(|err| PlayerError::InitError(format!("Failed to bind metrics endpoint: {}", err)))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|err| PlayerError::InitError(format!("Failed to bind metrics endpoint: {}", err)))?;
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let stopped = Arc::clone(&stop);
+    let thread = std::thread::Builder::new()
+        .name("rtsp-player-metrics".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream
+                    && let Err(err) = respond(stream)
+                {
+                    println!("Metrics request failed: {}", err);
+                }
+            }
+        })
+        .map_err(|err| PlayerError::InitError(format!("Failed to start metrics thread: {}", err)))?;
+
+    println!("Serving metrics on http://{}/metrics", addr);
+    Ok(MetricsServer { addr, stop, thread: Some(thread) })
+}
+
+fn respond(stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, nothing in them matters
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim_end().is_empty() {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics_text()),
+        (Some("GET"), Some(_)) => ("404 Not Found", "Not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method not allowed\n".to_string()),
+    };
+
+    let mut writer = &stream;
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_labels_every_player() {
+        let player = PlayerMetrics {
+            stream: "Front \"door\"".into(),
+            url: "rtsp://***@cam/live".into(),
+            state: Some(PlayerState::Playing),
+            stats: StreamStats { reconnects_total: 3, ..Default::default() },
+        };
+        let text = render(&[player]);
+        let labels = "stream=\"Front \\\"door\\\"\",url=\"rtsp://***@cam/live\"";
+
+        assert!(text.contains(&format!("rtsp_player_state{{{},state=\"playing\"}} 1", labels)));
+        assert!(text.contains(&format!("rtsp_player_state{{{},state=\"paused\"}} 0", labels)));
+        assert!(text.contains(&format!("rtsp_player_reconnects_total{{{}}} 3", labels)));
+        assert!(text.contains("# TYPE rtsp_player_uptime_seconds gauge"));
+    }
+}
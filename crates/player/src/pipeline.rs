@@ -0,0 +1,317 @@
+use crate::{PlayerError, RtspPlayerConfig, SrtpMode, VideoSinkKind};
+use gstreamer_video as gst_video;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+/// Build the playback pipeline element by element:
+///
+/// `rtspsrc ! rtpjitterbuffer ! <depay> ! <parse> ! tee ! queue ! decodebin ! videoconvert ! <video sink>`
+///
+/// plus, when audio is enabled and the server offers it,
+///
+/// `rtspsrc ! queue ! decodebin ! audioconvert ! audioresample ! volume ! <audio sink>`
+///
+/// H.264 and H.265 are depayloaded and parsed before the `tee` so a recording
+/// branch can remux them without decoding. Other codecs go to the `tee` as
+/// RTP and `decodebin` picks the depayloader. `rtspsrc` and `decodebin` expose
+/// their pads at runtime, so those links are made from `pad-added` handlers.
+pub(crate) fn build_pipeline(config: &RtspPlayerConfig, video_sink: &VideoSinkKind) -> std::result::Result<gst::Pipeline, PlayerError> {
+    let sink_name = video_sink
+        .factory_name()
+        .ok_or_else(|| PlayerError::InitError("Video sink must be resolved before building the pipeline".into()))?;
+    for factory in ["rtspsrc", "rtpjitterbuffer", "tee", "queue", "decodebin", "videoconvert", sink_name] {
+        require_element(factory)?;
+    }
+
+    let pipeline = gst::Pipeline::with_name("rtsp-player");
+
+    let mut src_builder = gst::ElementFactory::make("rtspsrc")
+        .name("src")
+        .property("location", config.url())
+        .property("latency", config.latency().as_millis() as u32)
+        .property("retry", config.retry() as u32)
+        .property("timeout", config.timeout().as_micros() as u64)
+        .property("tcp-timeout", config.tcp_timeout().as_micros() as u64)
+        .property_from_str("protocols", config.transport().to_gst_flags())
+        .property_from_str("buffer-mode", config.jitterbuffer_mode().nick());
+    if let Some((first, last)) = config.udp_port_range() {
+        src_builder = src_builder.property("port-range", format!("{}-{}", first, last));
+    }
+    if let Some(user_agent) = config.user_agent() {
+        src_builder = src_builder.property("user-agent", user_agent);
+    }
+    if let Some(credentials) = config.credentials() {
+        // rtspsrc answers Basic and Digest challenges with these
+        src_builder = src_builder
+            .property("user-id", credentials.username.as_str())
+            .property("user-pw", credentials.password.as_str());
+    }
+    let src = src_builder.build()?;
+    if config.url().starts_with("rtsps") {
+        if config.tls().srtp == SrtpMode::Required {
+            require_element("srtpdec")?;
+        }
+        config.tls().apply(&src)?;
+    }
+    if src.find_property("add-reference-timestamp-meta").is_some() {
+        // Lets frames carry the camera's NTP capture time
+        src.set_property("add-reference-timestamp-meta", true);
+    }
+
+    let mut jitterbuffer_builder = gst::ElementFactory::make("rtpjitterbuffer")
+        .name("jitterbuffer")
+        .property("latency", config.latency().as_millis() as u32);
+    if config.jitterbuffer_mode() != crate::JitterbufferMode::Auto {
+        // rtpjitterbuffer has no "auto" mode, leave its default in that case
+        jitterbuffer_builder = jitterbuffer_builder.property_from_str("mode", config.jitterbuffer_mode().nick());
+    }
+    let jitterbuffer = jitterbuffer_builder.build()?;
+
+    let tee = gst::ElementFactory::make("tee").name("tee").build()?;
+
+    let queue_limits = config.queue_limits();
+    let queue = gst::ElementFactory::make("queue")
+        .name("queue")
+        .property("max-size-buffers", queue_limits.max_buffers)
+        .property("max-size-bytes", queue_limits.max_bytes)
+        .property("max-size-time", queue_limits.max_time.as_nanos() as u64)
+        .build()?;
+
+    let decodebin = gst::ElementFactory::make("decodebin").name("decoder").build()?;
+    let convert = gst::ElementFactory::make("videoconvert").name("convert").build()?;
+
+    let sink = gst::ElementFactory::make(sink_name)
+        .name("videosink")
+        .property("sync", config.sync())
+        .build()?;
+    if matches!(video_sink, VideoSinkKind::App) {
+        // Hand out frames in the requested format and never let a slow
+        // application stall the pipeline
+        let caps = gst_video::VideoCapsBuilder::new()
+            .format(config.frame_format().video_format())
+            .build();
+        sink.set_property("caps", &caps);
+        sink.set_property("max-buffers", 2u32);
+        sink.set_property("drop", true);
+    }
+    if sink.find_property("enable-last-sample").is_some() {
+        // Keeps the frame on screen around for snapshots
+        sink.set_property("enable-last-sample", true);
+    }
+
+    pipeline.add_many([&src, &jitterbuffer, &tee, &queue, &decodebin, &convert, &sink])?;
+    gst::Element::link_many([&tee, &queue, &decodebin])?;
+    gst::Element::link_many([&convert, &sink])?;
+
+    if config.audio() {
+        // The rest of the audio branch is only built once the server offers
+        // audio, an unlinked sink would keep the pipeline from prerolling
+        require_element("volume")?;
+        let volume = gst::ElementFactory::make("volume")
+            .name("volume")
+            .property("volume", config.volume())
+            .property("mute", config.muted())
+            .build()?;
+        pipeline.add(&volume)?;
+    } else {
+        // Don't even set up audio streams with the server
+        src.connect("select-stream", false, |args| {
+            let is_audio = args[2]
+                .get::<gst::Caps>()
+                .ok()
+                .and_then(|caps| caps.structure(0).and_then(|s| s.get::<&str>("media").ok()).map(|m| m == "audio"))
+                .unwrap_or(false);
+            Some((!is_audio).to_value())
+        });
+    }
+
+    let pipeline_weak = pipeline.downgrade();
+    let audio_sink = config.audio_sink().to_string();
+    src.connect_pad_added(move |_, pad| {
+        let Some(pipeline) = pipeline_weak.upgrade() else { return };
+        if pad_has_media(pad, "audio") {
+            if let Err(err) = link_audio(&pipeline, pad, &audio_sink) {
+                println!("Failed to set up audio: {}", err);
+            }
+            return;
+        }
+        // Link the first video stream announced by the server to the jitterbuffer
+        if !pad_has_media(pad, "video") {
+            return;
+        }
+        let (Some(jitterbuffer), Some(tee)) = (pipeline.by_name("jitterbuffer"), pipeline.by_name("tee")) else { return };
+        let sink_pad = jitterbuffer.static_pad("sink").expect("rtpjitterbuffer has a sink pad");
+        if sink_pad.is_linked() {
+            return;
+        }
+        // Build the chain behind the jitterbuffer first, so no data reaches an unlinked pad
+        if let Err(err) = link_depayloader(&pipeline, pad, &jitterbuffer, &tee) {
+            println!("Failed to set up depayloader: {}", err);
+            unlink_depayloader(&pipeline, &jitterbuffer, &tee);
+            return;
+        }
+        if let Err(err) = pad.link(&sink_pad) {
+            println!("Failed to link RTSP source pad {}: {:?}", pad.name(), err);
+            unlink_depayloader(&pipeline, &jitterbuffer, &tee);
+        }
+    });
+
+    // Link the decoded video to the converter
+    let convert_weak = convert.downgrade();
+    decodebin.connect_pad_added(move |_, pad| {
+        let Some(convert) = convert_weak.upgrade() else { return };
+        let is_video = pad
+            .current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().starts_with("video/")))
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        let sink_pad = convert.static_pad("sink").expect("videoconvert has a sink pad");
+        if sink_pad.is_linked() {
+            return;
+        }
+        if let Err(err) = pad.link(&sink_pad) {
+            println!("Failed to link decoder pad {}: {:?}", pad.name(), err);
+        }
+    });
+
+    Ok(pipeline)
+}
+
+/// Build `queue ! decodebin ! audioconvert ! audioresample` in front of the
+/// `volume` element for the first audio stream and link it to `src_pad`.
+fn link_audio(pipeline: &gst::Pipeline, src_pad: &gst::Pad, audio_sink: &str) -> std::result::Result<(), PlayerError> {
+    let Some(volume) = pipeline.by_name("volume") else { return Ok(()) };
+    if pipeline.by_name("audiodecoder").is_some() {
+        // Only the first audio stream is played
+        return Ok(());
+    }
+    for factory in ["queue", "decodebin", "audioconvert", "audioresample", audio_sink] {
+        require_element(factory)?;
+    }
+
+    let encoding_name = src_pad
+        .current_caps()
+        .and_then(|caps| caps.structure(0).and_then(|s| s.get::<String>("encoding-name").ok()))
+        .unwrap_or_default();
+
+    let queue = gst::ElementFactory::make("queue").name("audioqueue").build()?;
+    let decodebin = gst::ElementFactory::make("decodebin").name("audiodecoder").build()?;
+    let convert = gst::ElementFactory::make("audioconvert").name("audioconvert").build()?;
+    let resample = gst::ElementFactory::make("audioresample").name("audioresample").build()?;
+    let sink = gst::ElementFactory::make(audio_sink).name("audiosink").build()?;
+
+    pipeline.add_many([&queue, &decodebin, &convert, &resample, &sink])?;
+    queue.link(&decodebin)?;
+    gst::Element::link_many([&convert, &resample, &volume, &sink])?;
+
+    let convert_weak = convert.downgrade();
+    decodebin.connect_pad_added(move |decodebin, pad| {
+        let Some(convert) = convert_weak.upgrade() else { return };
+        let Some(caps) = pad.current_caps() else { return };
+        let Some(structure) = caps.structure(0) else { return };
+        if !structure.name().starts_with("audio/") {
+            return;
+        }
+        let sink_pad = convert.static_pad("sink").expect("audioconvert has a sink pad");
+        if sink_pad.is_linked() {
+            return;
+        }
+        if let Err(err) = pad.link(&sink_pad) {
+            println!("Failed to link audio decoder pad {}: {:?}", pad.name(), err);
+            return;
+        }
+
+        // Report the stream on the bus, the player turns this into an AudioInfo event
+        let info = gst::Structure::builder("rtsp-player-audio-info")
+            .field("codec", encoding_name.as_str())
+            .field("rate", structure.get::<i32>("rate").unwrap_or(0))
+            .field("channels", structure.get::<i32>("channels").unwrap_or(0))
+            .build();
+        let _ = decodebin.post_message(gst::message::Element::builder(info).src(decodebin).build());
+    });
+
+    for element in [&sink, &volume, &resample, &convert, &decodebin, &queue] {
+        element.sync_state_with_parent()?;
+    }
+
+    let queue_sink = queue.static_pad("sink").expect("queue has a sink pad");
+    src_pad
+        .link(&queue_sink)
+        .map_err(|err| PlayerError::InitError(format!("Failed to link audio pad {}: {:?}", src_pad.name(), err)))?;
+    Ok(())
+}
+
+/// Depayloader and parser for codecs that can be recorded without re-encoding.
+pub(crate) fn codec_elements(encoding_name: &str) -> Option<(&'static str, &'static str)> {
+    match encoding_name.to_ascii_uppercase().as_str() {
+        "H264" => Some(("rtph264depay", "h264parse")),
+        "H265" => Some(("rtph265depay", "h265parse")),
+        _ => None,
+    }
+}
+
+/// Put `<depay> ! <parse>` between the jitterbuffer and the tee, or link them
+/// directly when the codec has no known depayloader.
+fn link_depayloader(
+    pipeline: &gst::Pipeline,
+    src_pad: &gst::Pad,
+    jitterbuffer: &gst::Element,
+    tee: &gst::Element,
+) -> std::result::Result<(), PlayerError> {
+    let caps = src_pad.current_caps().unwrap_or_else(|| src_pad.query_caps(None));
+    let encoding_name = caps
+        .structure(0)
+        .and_then(|s| s.get::<&str>("encoding-name").ok())
+        .unwrap_or("");
+
+    let elements = codec_elements(encoding_name)
+        .filter(|(depay, parse)| require_element(depay).is_ok() && require_element(parse).is_ok());
+    let Some((depay_name, parse_name)) = elements else {
+        println!("No depayloader for '{}', decoding RTP directly", encoding_name);
+        jitterbuffer.link(tee)?;
+        return Ok(());
+    };
+
+    let depay = gst::ElementFactory::make(depay_name).name("depay").build()?;
+    // Repeat SPS/PPS before every keyframe so recordings can start anywhere
+    let parse = gst::ElementFactory::make(parse_name)
+        .name("parse")
+        .property("config-interval", -1i32)
+        .build()?;
+
+    pipeline.add_many([&depay, &parse])?;
+    gst::Element::link_many([jitterbuffer, &depay, &parse, tee])?;
+    parse.sync_state_with_parent()?;
+    depay.sync_state_with_parent()?;
+    Ok(())
+}
+
+/// Undo `link_depayloader`, so the next video pad can try again.
+fn unlink_depayloader(pipeline: &gst::Pipeline, jitterbuffer: &gst::Element, tee: &gst::Element) {
+    for name in ["depay", "parse"] {
+        if let Some(element) = pipeline.by_name(name) {
+            let _ = element.set_state(gst::State::Null);
+            let _ = pipeline.remove(&element);
+        }
+    }
+    jitterbuffer.unlink(tee);
+}
+
+/// Fail with `MissingPlugin` when an element factory is not installed.
+pub(crate) fn require_element(factory: &str) -> std::result::Result<(), PlayerError> {
+    match gst::ElementFactory::find(factory) {
+        Some(_) => Ok(()),
+        None => Err(PlayerError::missing_element(factory)),
+    }
+}
+
+/// Whether an `rtspsrc` pad carries the given RTP media type ("video" or "audio").
+pub(crate) fn pad_has_media(pad: &gst::Pad, media: &str) -> bool {
+    let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+    caps.structure(0)
+        .and_then(|s| s.get::<&str>("media").ok())
+        .map(|m| m == media)
+        .unwrap_or(false)
+}
@@ -0,0 +1,977 @@
+use crate::dispatch::BusDispatcher;
+use crate::events::{EventBus, EventReceiver, EventStream, SubscriptionId};
+use crate::frames::{self, Frame, FrameHub, FrameReceiver};
+use crate::manager::DecoderLimit;
+use crate::reconnect::{ReconnectReason, ReconnectSupervisor, SupervisorCommand};
+use crate::recording::{Recording, RecordingRequest};
+use crate::stats::StatsCollector;
+use crate::tls;
+use crate::transport::FallbackWatch;
+use crate::watchdog::{Watchdog, WATCHDOG_INTERVAL};
+use crate::snapshot::{self, ImageFormat, PixelFormat, Snapshot};
+use crate::{pipeline, CertificateProblem, GstErrorInfo, PlayerError, PlayerEvent, RecordingFormat, RtspPlayerConfig, SegmentOptions, StreamStats, Transport, VideoInfo, VideoSinkKind};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video as gst_video;
+use gstreamer_video::prelude::VideoOverlayExtManual;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+
+/// The audio stream being played, reported once its decoder is linked.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AudioInfo {
+    /// RTP encoding name, e.g. `MPEG4-GENERIC` for AAC or `PCMU`.
+    pub codec: String,
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+/// Platform-neutral RTSP playback engine.
+///
+/// Owns the pipeline and dispatches its bus messages on a dedicated thread, so
+/// no GLib or Win32 main loop is needed in the host application. Rendering
+/// into an existing window goes through [`RtspPlayer::set_window_handle`].
+#[derive(Debug)]
+pub struct RtspPlayer {
+    shared: Arc<Shared>,
+    supervisor: ReconnectSupervisor,
+    /// Set for players of a [`crate::PlayerManager`] with a decoder limit.
+    decoder_limit: Option<Arc<DecoderLimit>>,
+}
+
+/// Player state reachable from the bus thread and the reconnect supervisor.
+///
+/// The pipeline sits behind a mutex because a reconnect may replace it with a
+/// freshly built one.
+#[derive(Debug)]
+pub(crate) struct Shared {
+    pub(crate) config: RtspPlayerConfig,
+    pub(crate) video_sink: VideoSinkKind,
+    pipeline: Mutex<gst::Pipeline>,
+    is_playing: Mutex<bool>,
+    /// Set from the first start until the player stops, paused or not.
+    holds_decoder: AtomicBool,
+    pub(crate) reconnect_attempts: Mutex<u32>,
+    pub(crate) reconnects_total: AtomicU64,
+    stalls_total: AtomicU64,
+    created_at: Instant,
+    video_info: Mutex<Option<VideoInfo>>,
+    audio_info: Mutex<Option<AudioInfo>>,
+    volume: Mutex<f64>,
+    muted: Mutex<bool>,
+    position: Mutex<u64>,
+    duration: Mutex<u64>,
+    window_handle: Mutex<Option<usize>>,
+    recording: Mutex<Option<Recording>>,
+    /// A recording interrupted by a reconnect, started again once the stream
+    /// plays, and the last file it wrote. Lock before `recording` when holding both.
+    suspended_recording: Mutex<Option<(RecordingRequest, PathBuf)>>,
+    frames: Arc<FrameHub>,
+    pub(crate) events: EventBus,
+    bus_watch: Mutex<Option<glib::Source>>,
+    stats: Arc<StatsCollector>,
+    last_stats: Mutex<StreamStats>,
+    stats_timer: Mutex<Option<glib::Source>>,
+    watchdog: Arc<Watchdog>,
+    watchdog_timer: Mutex<Option<glib::Source>>,
+    transport: Mutex<Transport>,
+    fallback: Arc<FallbackWatch>,
+    fallback_timer: Mutex<Option<glib::Source>>,
+    reconnect_requests: Sender<SupervisorCommand>,
+    dispatcher: Arc<BusDispatcher>,
+}
+
+impl RtspPlayer {
+    pub fn new(url: &str) -> std::result::Result<Self, PlayerError> {
+        Self::with_config(RtspPlayerConfig::builder(url).build()?)
+    }
+
+    /// Create a player rendering to the given video sink.
+    ///
+    /// `VideoSinkKind::Auto` picks the best sink for the platform; with
+    /// `headless` set it resolves to `fakesink` instead of a window.
+    pub fn with_video_sink(url: &str, video_sink: VideoSinkKind, headless: bool) -> std::result::Result<Self, PlayerError> {
+        Self::with_config(
+            RtspPlayerConfig::builder(url)
+                .video_sink(video_sink)
+                .headless(headless)
+                .build()?,
+        )
+    }
+
+    pub fn with_config(config: RtspPlayerConfig) -> std::result::Result<Self, PlayerError> {
+        Self::with_dispatcher(config, Arc::new(BusDispatcher::spawn("rtsp-player-bus")?))
+    }
+
+    /// Create a player whose bus messages and timers run on `dispatcher`,
+    /// which may be shared with other players.
+    pub(crate) fn with_dispatcher(config: RtspPlayerConfig, dispatcher: Arc<BusDispatcher>) -> std::result::Result<Self, PlayerError> {
+        // Initialize GStreamer if not already initialized
+        if gst::init().is_err() {
+            return Err(PlayerError::InitError("Failed to initialize GStreamer".into()));
+        }
+
+        let video_sink = config.video_sink().resolve(config.headless())?;
+        println!("Creating player for {}", config.display_name());
+        println!("Using video sink: {}", video_sink);
+
+        let pipeline = pipeline::build_pipeline(&config, &video_sink)?;
+        let (reconnect_requests, reconnect_receiver) = channel();
+        let (volume, muted) = (config.volume(), config.muted());
+        let transport = config.transport();
+
+        let shared = Arc::new(Shared {
+            config,
+            video_sink,
+            pipeline: Mutex::new(pipeline),
+            is_playing: Mutex::new(false),
+            holds_decoder: AtomicBool::new(false),
+            reconnect_attempts: Mutex::new(0),
+            reconnects_total: AtomicU64::new(0),
+            stalls_total: AtomicU64::new(0),
+            created_at: Instant::now(),
+            video_info: Mutex::new(None),
+            audio_info: Mutex::new(None),
+            volume: Mutex::new(volume),
+            muted: Mutex::new(muted),
+            position: Mutex::new(0),
+            duration: Mutex::new(0),
+            window_handle: Mutex::new(None),
+            recording: Mutex::new(None),
+            suspended_recording: Mutex::new(None),
+            frames: Arc::new(FrameHub::default()),
+            events: EventBus::default(),
+            bus_watch: Mutex::new(None),
+            stats: Arc::new(StatsCollector::default()),
+            last_stats: Mutex::new(StreamStats::default()),
+            stats_timer: Mutex::new(None),
+            watchdog: Arc::new(Watchdog::default()),
+            watchdog_timer: Mutex::new(None),
+            transport: Mutex::new(transport),
+            fallback: Arc::new(FallbackWatch::default()),
+            fallback_timer: Mutex::new(None),
+            reconnect_requests: reconnect_requests.clone(),
+            dispatcher,
+        });
+        shared.watch_bus()?;
+        shared.watchdog.attach(&shared.pipeline())?;
+        shared.fallback.attach(&shared.pipeline())?;
+        shared.stats.attach(&shared.pipeline())?;
+        shared.attach_frames(&shared.pipeline())?;
+        shared.watch_caps(&shared.pipeline());
+        shared.setup_stats_timer();
+        shared.setup_watchdog_timer();
+        shared.setup_fallback_timer();
+        #[cfg(feature = "metrics")]
+        crate::metrics::register(&shared);
+
+        let supervisor = ReconnectSupervisor::spawn(Arc::downgrade(&shared), reconnect_requests, reconnect_receiver)?;
+
+        Ok(RtspPlayer { shared, supervisor, decoder_limit: None })
+    }
+
+    /// Make [`RtspPlayer::play`], [`RtspPlayer::pause`] and [`RtspPlayer::resume`] respect `limit`.
+    pub(crate) fn limit_decoders(mut self, limit: Arc<DecoderLimit>) -> Self {
+        limit.register(&self.shared);
+        self.decoder_limit = Some(limit);
+        self
+    }
+
+    pub fn config(&self) -> &RtspPlayerConfig {
+        &self.shared.config
+    }
+
+    /// The video sink the pipeline was built with.
+    pub fn video_sink(&self) -> &VideoSinkKind {
+        &self.shared.video_sink
+    }
+
+    /// Render into an existing native window (HWND, X11 window id, ...).
+    ///
+    /// Fails if the selected video sink cannot be embedded. The handle is
+    /// reapplied whenever a reconnect rebuilds the pipeline.
+    pub fn set_window_handle(&self, handle: usize) -> std::result::Result<(), PlayerError> {
+        self.shared.apply_window_handle(&self.shared.pipeline(), handle)?;
+        *self.shared.window_handle.lock().unwrap() = Some(handle);
+        Ok(())
+    }
+
+    /// Start playing. Fails with [`PlayerError::LimitReached`] if the player
+    /// belongs to a [`crate::PlayerManager`] whose decoders are all in use.
+    pub fn play(&self) -> std::result::Result<(), PlayerError> {
+        self.with_decoder(|| {
+            self.shared.pipeline().set_state(gst::State::Playing)?;
+            *self.shared.is_playing.lock().unwrap() = true;
+            self.shared.watchdog.arm();
+            self.shared.fallback.arm();
+            Ok(())
+        })
+    }
+
+    /// Pause playback. A paused player keeps its decoder, so like
+    /// [`RtspPlayer::play`] this can fail with [`PlayerError::LimitReached`].
+    pub fn pause(&self) -> std::result::Result<(), PlayerError> {
+        self.with_decoder(|| {
+            self.shared.watchdog.disarm();
+            self.shared.fallback.disarm();
+            self.shared.pipeline().set_state(gst::State::Paused)?;
+            *self.shared.is_playing.lock().unwrap() = false;
+            Ok(())
+        })
+    }
+
+    /// Run `start`, which brings the pipeline up, within the decoder limit.
+    fn with_decoder<F>(&self, start: F) -> std::result::Result<(), PlayerError>
+    where
+        F: FnOnce() -> std::result::Result<(), PlayerError>,
+    {
+        let start = || {
+            start()?;
+            self.shared.holds_decoder.store(true, Ordering::Relaxed);
+            Ok(())
+        };
+        match &self.decoder_limit {
+            Some(limit) => limit.start(&self.shared, start),
+            None => start(),
+        }
+    }
+
+    pub fn resume(&self) -> std::result::Result<(), PlayerError> {
+        self.play()
+    }
+
+    /// Stop playback. Also cancels a pending reconnect and finalizes a running recording.
+    pub fn stop(&self) -> std::result::Result<(), PlayerError> {
+        *self.shared.is_playing.lock().unwrap() = false;
+        *self.shared.reconnect_attempts.lock().unwrap() = 0;
+        self.shared.watchdog.disarm();
+        self.shared.fallback.disarm();
+        let recording = self.shared.finalize_recording();
+        self.shared.pipeline().set_state(gst::State::Null)?;
+        self.shared.holds_decoder.store(false, Ordering::Relaxed);
+        recording.map(|_| ())
+    }
+
+    /// Record the stream to `path` while it keeps playing, without re-encoding.
+    ///
+    /// The container follows the extension, see [`RecordingFormat::from_path`].
+    /// Only H.264 and H.265 streams can be recorded, and only once the stream
+    /// has started. A reconnect closes the file and recording continues in
+    /// `<name>-1.<ext>`, `<name>-2.<ext>`, ... once the stream is back.
+    pub fn start_recording(&self, path: impl AsRef<Path>) -> std::result::Result<(), PlayerError> {
+        let path = path.as_ref();
+        self.start_recording_as(path, RecordingFormat::from_path(path))
+    }
+
+    pub fn start_recording_as(&self, path: impl AsRef<Path>, format: RecordingFormat) -> std::result::Result<(), PlayerError> {
+        self.begin_recording(RecordingRequest::file(path.as_ref(), format))
+    }
+
+    /// Record continuously into files rotated by time or size, deleting old
+    /// ones according to the retention policy. Stop with [`RtspPlayer::stop_recording`].
+    ///
+    /// A reconnect closes the current segment; recording goes on with the next
+    /// index once the stream is back.
+    pub fn start_segmented_recording(&self, options: SegmentOptions) -> std::result::Result<(), PlayerError> {
+        self.begin_recording(RecordingRequest::Segmented { options, start_index: 0 })
+    }
+
+    fn begin_recording(&self, request: RecordingRequest) -> std::result::Result<(), PlayerError> {
+        let path = {
+            let suspended = self.shared.suspended_recording.lock().unwrap();
+            let mut recording = self.shared.recording.lock().unwrap();
+            if recording.is_some() || suspended.is_some() {
+                return Err(PlayerError::RecordingError("A recording is already running".into()));
+            }
+            let started = request.start(&self.shared.pipeline())?;
+            let path = started.path().to_path_buf();
+            *recording = Some(started);
+            path
+        };
+
+        println!("Recording to {}", path.display());
+        self.shared.events.emit(PlayerEvent::RecordingStarted { path });
+        Ok(())
+    }
+
+    /// Finalize the running recording. Returns its path, or `None` if nothing was recorded.
+    pub fn stop_recording(&self) -> std::result::Result<Option<PathBuf>, PlayerError> {
+        self.shared.finalize_recording()
+    }
+
+    /// Also true while a reconnect interrupts the recording.
+    pub fn is_recording(&self) -> bool {
+        self.shared.suspended_recording.lock().unwrap().is_some() || self.shared.recording.lock().unwrap().is_some()
+    }
+
+    pub fn seek(&self, position_percent: f64) -> std::result::Result<(), PlayerError> {
+        let duration = *self.shared.duration.lock().unwrap();
+        if duration > 0 {
+            let position = gst::ClockTime::from_seconds((position_percent * duration as f64) as u64);
+            self.shared.pipeline().seek_simple(
+                gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+                position,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Query the current position and duration in seconds.
+    ///
+    /// Returns `None` while not playing or when the pipeline cannot answer,
+    /// e.g. for live streams without a known duration.
+    pub fn update_position(&self) -> Option<(u64, u64)> {
+        if !self.is_playing() {
+            return None;
+        }
+
+        let pipeline = self.shared.pipeline();
+        let pos_secs = pipeline.query_position::<gst::ClockTime>()?.seconds();
+        *self.shared.position.lock().unwrap() = pos_secs;
+
+        let dur_secs = pipeline.query_duration::<gst::ClockTime>()?.seconds();
+        *self.shared.duration.lock().unwrap() = dur_secs;
+
+        Some((pos_secs, dur_secs))
+    }
+
+    /// Last known position in seconds.
+    pub fn position(&self) -> u64 {
+        *self.shared.position.lock().unwrap()
+    }
+
+    /// Last known duration in seconds, 0 for live streams.
+    pub fn duration(&self) -> u64 {
+        *self.shared.duration.lock().unwrap()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.shared.is_playing()
+    }
+
+    pub(crate) fn holds_decoder(&self) -> bool {
+        self.shared.holds_decoder()
+    }
+
+    /// The transport in use, which differs from the configured one after a
+    /// fallback to TCP.
+    pub fn transport(&self) -> Transport {
+        self.shared.transport()
+    }
+
+    /// The last decoded frame as RGBA.
+    ///
+    /// Works with every sink that keeps its last sample, including `fakesink`
+    /// in headless mode.
+    pub fn snapshot(&self) -> std::result::Result<Snapshot, PlayerError> {
+        self.snapshot_as(PixelFormat::Rgba)
+    }
+
+    pub fn snapshot_as(&self, format: PixelFormat) -> std::result::Result<Snapshot, PlayerError> {
+        snapshot::capture(&self.shared.pipeline(), format)
+    }
+
+    /// Write the last decoded frame to `path` as PNG or JPEG.
+    pub fn snapshot_to_file(&self, path: impl AsRef<Path>, format: ImageFormat) -> std::result::Result<(), PlayerError> {
+        let path = path.as_ref();
+        let image = snapshot::encode(&self.shared.pipeline(), format)?;
+        std::fs::write(path, image)
+            .map_err(|err| PlayerError::SnapshotError(format!("Cannot write {}: {}", path.display(), err)))
+    }
+
+    /// Iterate over decoded frames. Requires `VideoSinkKind::App`, see
+    /// [`crate::RtspPlayerConfigBuilder::frames_only`].
+    pub fn frames(&self) -> std::result::Result<FrameReceiver, PlayerError> {
+        self.require_frames()?;
+        Ok(FrameReceiver::new(&self.shared.frames, self.shared.config.frame_queue_size()))
+    }
+
+    /// Call `callback` with every decoded frame on a dedicated thread.
+    ///
+    /// A slow callback only causes frames to be dropped for itself, never for
+    /// the stream or other subscribers.
+    pub fn on_frame<F>(&self, callback: F) -> std::result::Result<SubscriptionId, PlayerError>
+    where
+        F: FnMut(&Frame) + Send + 'static,
+    {
+        self.require_frames()?;
+        frames::spawn_callback(&self.shared.frames, self.shared.config.frame_queue_size(), callback)
+    }
+
+    /// Stop a callback registered with [`RtspPlayer::on_frame`].
+    pub fn remove_frame_callback(&self, id: SubscriptionId) -> bool {
+        self.shared.frames.unsubscribe(id)
+    }
+
+    fn require_frames(&self) -> std::result::Result<(), PlayerError> {
+        if self.shared.video_sink != VideoSinkKind::App {
+            return Err(PlayerError::ConfigError("Frame delivery requires the appsink video sink".into()));
+        }
+        Ok(())
+    }
+
+    /// Set the audio volume, 1.0 is unchanged and 10.0 the maximum.
+    pub fn set_volume(&self, volume: f64) -> std::result::Result<(), PlayerError> {
+        if !(0.0..=crate::config::MAX_VOLUME).contains(&volume) {
+            return Err(PlayerError::ConfigError("Volume must be between 0.0 and 10.0".into()));
+        }
+        *self.shared.volume.lock().unwrap() = volume;
+        self.shared.apply_audio_settings(&self.shared.pipeline());
+        Ok(())
+    }
+
+    pub fn volume(&self) -> f64 {
+        *self.shared.volume.lock().unwrap()
+    }
+
+    pub fn mute(&self) {
+        *self.shared.muted.lock().unwrap() = true;
+        self.shared.apply_audio_settings(&self.shared.pipeline());
+    }
+
+    pub fn unmute(&self) {
+        *self.shared.muted.lock().unwrap() = false;
+        self.shared.apply_audio_settings(&self.shared.pipeline());
+    }
+
+    pub fn is_muted(&self) -> bool {
+        *self.shared.muted.lock().unwrap()
+    }
+
+    /// The audio stream being played, `None` without audio.
+    pub fn audio_info(&self) -> Option<AudioInfo> {
+        self.shared.audio_info.lock().unwrap().clone()
+    }
+
+    /// Subscribe to player events through a blocking iterator.
+    pub fn subscribe(&self) -> EventReceiver {
+        self.shared.events.subscribe()
+    }
+
+    /// Register a callback invoked for every event.
+    ///
+    /// Callbacks run on the player's bus or reconnect thread and should return quickly.
+    pub fn on_event<F>(&self, callback: F) -> SubscriptionId
+    where
+        F: Fn(&PlayerEvent) + Send + Sync + 'static,
+    {
+        self.shared.events.on_event(callback)
+    }
+
+    /// Subscribe to player events as an async `Stream`.
+    pub fn event_stream(&self) -> EventStream {
+        self.shared.events.stream()
+    }
+
+    /// Remove a callback registered with [`RtspPlayer::on_event`].
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        self.shared.events.unsubscribe(id)
+    }
+
+    /// Properties of the video stream, `None` until caps have been negotiated.
+    pub fn video_info(&self) -> Option<VideoInfo> {
+        self.shared.video_info.lock().unwrap().clone()
+    }
+
+    /// Statistics as of the last stats interval, the same values the latest
+    /// `PlayerEvent::Stats` carried.
+    pub fn stats(&self) -> StreamStats {
+        self.shared.last_stats.lock().unwrap().clone()
+    }
+}
+
+impl Shared {
+    /// The pipeline currently in use.
+    pub(crate) fn pipeline(&self) -> gst::Pipeline {
+        self.pipeline.lock().unwrap().clone()
+    }
+
+    /// Whether the user wants the stream running.
+    pub(crate) fn is_playing(&self) -> bool {
+        *self.is_playing.lock().unwrap()
+    }
+
+    /// Whether the pipeline is built and counts against a decoder limit.
+    pub(crate) fn holds_decoder(&self) -> bool {
+        self.holds_decoder.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn request_reconnect(&self, reason: ReconnectReason) {
+        let _ = self.reconnect_requests.send(SupervisorCommand::Reconnect(reason));
+    }
+
+    /// Tear the stream down while keeping the intent to play, and to record.
+    pub(crate) fn halt(&self) {
+        self.watchdog.disarm();
+        self.fallback.disarm();
+        self.suspend_recording();
+        let _ = self.pipeline().set_state(gst::State::Null);
+    }
+
+    /// Stop for a failure that reconnecting cannot fix.
+    fn stop_without_reconnect(&self) {
+        *self.is_playing.lock().unwrap() = false;
+        *self.reconnect_attempts.lock().unwrap() = 0;
+        self.halt();
+        self.holds_decoder.store(false, Ordering::Relaxed);
+        // Nothing will resume the recording
+        let _ = self.finalize_recording();
+    }
+
+    pub(crate) fn give_up(&self, attempts: u32) {
+        self.stop_without_reconnect();
+        self.events.emit(PlayerEvent::ReconnectGaveUp { attempts });
+    }
+
+    /// Close the running recording without waiting for it, and remember it
+    /// for `resume_recording`.
+    fn suspend_recording(&self) {
+        let mut suspended = self.suspended_recording.lock().unwrap();
+        let Some(recording) = self.recording.lock().unwrap().take() else { return };
+        println!("Recording interrupted, it continues once the stream is back");
+        *suspended = Some((recording.next_request(), recording.path().to_path_buf()));
+
+        let events = self.events.clone();
+        recording.finalize_in_background(&self.pipeline(), move |result| match result {
+            Ok(path) => println!("Recording part written: {}", path.display()),
+            Err(err) => {
+                println!("{}", err);
+                events.emit(PlayerEvent::Error(err));
+            }
+        });
+    }
+
+    /// Start a recording interrupted by a reconnect again, once the stream plays.
+    fn resume_recording(&self) {
+        let result = {
+            let mut suspended = self.suspended_recording.lock().unwrap();
+            let Some((request, last_path)) = suspended.take() else { return };
+            match request.start(&self.pipeline()) {
+                Ok(started) => {
+                    let path = started.path().to_path_buf();
+                    *self.recording.lock().unwrap() = Some(started);
+                    Ok(path)
+                }
+                Err(err) => Err((err, last_path)),
+            }
+        };
+
+        match result {
+            Ok(path) => {
+                println!("Recording resumed to {}", path.display());
+                self.events.emit(PlayerEvent::RecordingStarted { path });
+            }
+            Err((err, last_path)) => {
+                println!("Could not resume recording: {}", err);
+                self.events.emit(PlayerEvent::Error(err));
+                self.events.emit(PlayerEvent::RecordingStopped { path: last_path });
+            }
+        }
+    }
+
+    /// Close the running recording, if any, so the file is complete. Also
+    /// ends a recording waiting for a reconnect.
+    pub(crate) fn finalize_recording(&self) -> std::result::Result<Option<PathBuf>, PlayerError> {
+        let suspended = self.suspended_recording.lock().unwrap().take();
+        let recording = self.recording.lock().unwrap().take();
+        let (path, result) = match (recording, suspended) {
+            (Some(recording), _) => {
+                let path = recording.path().to_path_buf();
+                (path, recording.finalize(&self.pipeline()).map(|_| ()))
+            }
+            // The last file was already finalized when the stream broke
+            (None, Some((_, last_path))) => (last_path, Ok(())),
+            (None, None) => return Ok(None),
+        };
+
+        println!("Recording stopped: {}", path.display());
+        self.events.emit(PlayerEvent::RecordingStopped { path: path.clone() });
+        result.map(|()| Some(path))
+    }
+
+    /// Start streaming again, optionally on a newly built pipeline.
+    pub(crate) fn restart(self: &Arc<Self>, rebuild: bool) -> std::result::Result<(), PlayerError> {
+        if rebuild {
+            self.rebuild_pipeline()?;
+        }
+        if let Some(src) = self.pipeline().by_name("src") {
+            // May have changed since the pipeline was built, after a fallback to TCP
+            src.set_property_from_str("protocols", self.transport().to_gst_flags());
+        }
+        self.pipeline().set_state(gst::State::Playing)?;
+        self.watchdog.arm();
+        self.fallback.arm();
+        Ok(())
+    }
+
+    /// Replace the pipeline with a fresh one built from the same configuration.
+    fn rebuild_pipeline(self: &Arc<Self>) -> std::result::Result<(), PlayerError> {
+        println!("Rebuilding pipeline");
+        let pipeline = pipeline::build_pipeline(&self.config, &self.video_sink)?;
+        if let Some(handle) = *self.window_handle.lock().unwrap() {
+            self.apply_window_handle(&pipeline, handle)?;
+        }
+        self.watchdog.attach(&pipeline)?;
+        self.fallback.attach(&pipeline)?;
+        self.stats.attach(&pipeline)?;
+        self.attach_frames(&pipeline)?;
+        self.apply_audio_settings(&pipeline);
+        self.watch_caps(&pipeline);
+
+        let old = std::mem::replace(&mut *self.pipeline.lock().unwrap(), pipeline);
+        let _ = old.set_state(gst::State::Null);
+        self.watch_bus()
+    }
+
+    /// Follow caps (re)negotiation before and after the decoder to keep
+    /// [`VideoInfo`] current, including mid-stream changes.
+    fn watch_caps(self: &Arc<Self>, pipeline: &gst::Pipeline) {
+        for (element, decoded) in [("convert", true), ("tee", false)] {
+            let Some(pad) = pipeline.by_name(element).and_then(|e| e.static_pad("sink")) else { continue };
+            let shared = Arc::downgrade(self);
+            pad.connect_notify(Some("caps"), move |pad, _| {
+                let (Some(shared), Some(caps)) = (shared.upgrade(), pad.current_caps()) else { return };
+                shared.update_video_info(&caps, decoded);
+            });
+        }
+    }
+
+    fn update_video_info(&self, caps: &gst::Caps, decoded: bool) {
+        let info = {
+            let mut current = self.video_info.lock().unwrap();
+            let mut info = current.clone().unwrap_or_default();
+            let updated = if decoded { info.update_from_decoded(caps) } else { info.update_from_encoded(caps) };
+            if !updated || current.as_ref() == Some(&info) {
+                return;
+            }
+            *current = Some(info.clone());
+            info
+        };
+
+        println!("Video info: {}x{} @ {:.2} fps, codec: {}", info.width, info.height, info.framerate, info.codec);
+        // Caps are negotiated on streaming threads, keep events on the bus thread
+        let events = self.events.clone();
+        self.dispatcher.invoke(move || events.emit(PlayerEvent::VideoInfo(info)));
+    }
+
+    /// Push the current volume and mute state to the `volume` element.
+    fn apply_audio_settings(&self, pipeline: &gst::Pipeline) {
+        let Some(volume) = pipeline.by_name("volume") else { return };
+        volume.set_property("volume", *self.volume.lock().unwrap());
+        volume.set_property("mute", *self.muted.lock().unwrap());
+    }
+
+    fn attach_frames(&self, pipeline: &gst::Pipeline) -> std::result::Result<(), PlayerError> {
+        if self.video_sink != VideoSinkKind::App {
+            return Ok(());
+        }
+        self.frames.attach(pipeline, self.config.frame_format())
+    }
+
+    fn apply_window_handle(&self, pipeline: &gst::Pipeline, handle: usize) -> std::result::Result<(), PlayerError> {
+        let video_sink = pipeline
+            .by_name("videosink")
+            .ok_or_else(|| PlayerError::SinkError(GstErrorInfo::local("Could not find video sink")))?;
+
+        let overlay = video_sink
+            .dynamic_cast::<gst_video::VideoOverlay>()
+            .map_err(|_| PlayerError::SinkError(GstErrorInfo::local(format!("Video sink {} does not support window embedding", self.video_sink))))?;
+
+        unsafe { overlay.set_window_handle(handle) };
+        Ok(())
+    }
+
+    /// Watch the bus of the current pipeline, replacing any previous watch.
+    fn watch_bus(self: &Arc<Self>) -> std::result::Result<(), PlayerError> {
+        let bus = self.pipeline().bus().ok_or_else(||
+            PlayerError::InitError("Failed to get pipeline bus".into())
+        )?;
+
+        let shared = Arc::downgrade(self);
+        let bus_watch = self.dispatcher.watch_bus(&bus, move |_, msg| {
+            match shared.upgrade() {
+                Some(shared) => {
+                    shared.handle_message(msg);
+                    glib::ControlFlow::Continue
+                }
+                None => glib::ControlFlow::Break,
+            }
+        });
+
+        if let Some(old) = self.bus_watch.lock().unwrap().replace(bus_watch) {
+            old.destroy();
+        }
+        Ok(())
+    }
+
+    fn handle_message(&self, msg: &gst::Message) {
+        use gstreamer::MessageView;
+
+        match msg.view() {
+            MessageView::Eos(..) => {
+                println!("End of stream");
+                self.events.emit(PlayerEvent::EndOfStream);
+                if self.config.reconnect_policy().reconnect_on_eos && self.is_playing() {
+                    self.request_reconnect(ReconnectReason::EndOfStream);
+                } else {
+                    self.watchdog.disarm();
+                    *self.is_playing.lock().unwrap() = false;
+                }
+            }
+            MessageView::Error(err) => {
+                if self.abort_failed_recording(msg) {
+                    let error = PlayerError::RecordingError(err.error().to_string());
+                    println!("{}", error);
+                    self.events.emit(PlayerEvent::Error(error));
+                    return;
+                }
+
+                let error = PlayerError::from_error_message(err);
+                println!("Error [{:?}]: {} ({:?})", error.code(), error, error.gst_info().and_then(|info| info.debug.as_deref()));
+
+                let auth_failed = matches!(error, PlayerError::AuthenticationError(_));
+                self.events.emit(PlayerEvent::Error(error));
+
+                if auth_failed {
+                    // Retrying with the same credentials cannot succeed and may lock the account
+                    let username = self.config.credentials().map(|credentials| credentials.username.clone());
+                    println!("Authentication failed, stopping");
+                    self.stop_without_reconnect();
+                    self.events.emit(PlayerEvent::AuthenticationFailed { username });
+                    return;
+                }
+
+                // If currently playing, let the supervisor bring the stream back
+                if self.is_playing() {
+                    self.request_reconnect(ReconnectReason::Error);
+                }
+            }
+            MessageView::StateChanged(state_changed) => {
+                // Only process messages from the pipeline
+                if msg.src() == Some(self.pipeline().upcast_ref::<gst::Object>()) {
+                    self.events.emit(PlayerEvent::StateChanged {
+                        old: state_changed.old().into(),
+                        current: state_changed.current().into(),
+                    });
+
+                    if state_changed.current() == gst::State::Playing {
+                        // The tee has caps now, so a recording branch can link
+                        self.resume_recording();
+
+                        // Reset reconnect counter when we successfully reach playing state
+                        let attempts = std::mem::take(&mut *self.reconnect_attempts.lock().unwrap());
+                        if attempts > 0 {
+                            println!("Reconnected after {} attempt(s)", attempts);
+                            self.events.emit(PlayerEvent::Reconnected { attempts });
+                        }
+                    }
+                }
+            }
+            MessageView::StreamStart(_) => {
+                println!("Stream started successfully");
+                self.events.emit(PlayerEvent::StreamStarted);
+            }
+            MessageView::Buffering(buffering) => {
+                let percent = buffering.percent();
+                println!("Buffering... {}%", percent);
+
+                self.events.emit(PlayerEvent::Buffering(percent));
+
+                // Pause the pipeline if buffering and resume when done
+                if percent < 100 {
+                    let _ = self.pipeline().set_state(gst::State::Paused);
+                } else if self.is_playing() {
+                    let _ = self.pipeline().set_state(gst::State::Playing);
+                }
+            }
+            MessageView::Element(element) => {
+                if let Some(structure) = element.structure()
+                    && structure.name().starts_with("splitmuxsink-fragment-")
+                {
+                    let closed = self.recording
+                        .lock()
+                        .unwrap()
+                        .as_mut()
+                        .and_then(|recording| recording.handle_segment_message(structure));
+                    if let Some(path) = closed {
+                        println!("Segment written: {}", path.display());
+                        self.events.emit(PlayerEvent::SegmentClosed { path });
+                    }
+                }
+
+                if let Some(structure) = element.structure()
+                    && structure.name() == tls::CERTIFICATE_MESSAGE
+                {
+                    let problems = CertificateProblem::from_bits(structure.get::<u32>("errors").unwrap_or(0));
+                    let accepted = structure.get::<bool>("accepted").unwrap_or(false);
+                    println!("Server certificate problems {:?}, {}", problems, if accepted { "accepted" } else { "rejected" });
+                    if !accepted {
+                        // The same certificate will be presented again on every reconnect
+                        self.stop_without_reconnect();
+                    }
+                    self.events.emit(PlayerEvent::CertificateError { problems, accepted });
+                }
+
+                if let Some(structure) = element.structure()
+                    && structure.name() == "rtsp-player-audio-info"
+                {
+                    let info = AudioInfo {
+                        codec: structure.get::<String>("codec").unwrap_or_default(),
+                        sample_rate: structure.get::<i32>("rate").unwrap_or(0).max(0) as u32,
+                        channels: structure.get::<i32>("channels").unwrap_or(0).max(0) as u32,
+                    };
+                    println!("Audio info: {} {} Hz, {} channel(s)", info.codec, info.sample_rate, info.channels);
+                    *self.audio_info.lock().unwrap() = Some(info.clone());
+                    self.events.emit(PlayerEvent::AudioInfo(info));
+                }
+            }
+            MessageView::Qos(qos) => {
+                self.stats.record_qos(qos);
+            }
+            _ => (),
+        }
+    }
+
+    /// Drop the recording branch if it posted `msg`. The display keeps running.
+    fn abort_failed_recording(&self, msg: &gst::Message) -> bool {
+        let Some(src) = msg.src() else { return false };
+        let failed = {
+            let mut recording = self.recording.lock().unwrap();
+            match recording.as_ref() {
+                Some(current) if current.contains(src) => recording.take(),
+                _ => None,
+            }
+        };
+        let Some(failed) = failed else { return false };
+
+        let path = failed.abort(&self.pipeline());
+        self.events.emit(PlayerEvent::RecordingStopped { path });
+        true
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) fn metrics(&self) -> crate::metrics::PlayerMetrics {
+        crate::metrics::PlayerMetrics {
+            stream: self.config.display_name(),
+            url: crate::redact::redact_url(self.config.url()),
+            state: Some(self.pipeline().current_state().into()),
+            stats: self.last_stats.lock().unwrap().clone(),
+        }
+    }
+
+    /// Refresh the statistics every stats interval and emit them while playing.
+    fn setup_stats_timer(self: &Arc<Self>) {
+        let shared: Weak<Shared> = Arc::downgrade(self);
+
+        let timer = self.dispatcher.schedule(self.config.stats_interval(), move || {
+            let Some(shared) = shared.upgrade() else { return glib::ControlFlow::Break };
+            let stats = StreamStats {
+                reconnect_attempts: *shared.reconnect_attempts.lock().unwrap(),
+                reconnects_total: shared.reconnects_total.load(Ordering::Relaxed),
+                stalls: shared.stalls_total.load(Ordering::Relaxed),
+                uptime: shared.created_at.elapsed(),
+                ..shared.stats.collect(&shared.pipeline())
+            };
+            *shared.last_stats.lock().unwrap() = stats.clone();
+            if shared.is_playing() {
+                shared.events.emit(PlayerEvent::Stats(stats));
+            }
+            glib::ControlFlow::Continue
+        });
+        *self.stats_timer.lock().unwrap() = Some(timer);
+    }
+
+    /// Reconnect when the watchdog sees no decoded frames for the stall timeout.
+    fn setup_watchdog_timer(self: &Arc<Self>) {
+        let Some(stall_timeout) = self.config.stall_timeout() else { return };
+        let shared: Weak<Shared> = Arc::downgrade(self);
+
+        let timer = self.dispatcher.schedule(WATCHDOG_INTERVAL, move || {
+            let Some(shared) = shared.upgrade() else { return glib::ControlFlow::Break };
+            if let Some(idle) = shared.watchdog.check(stall_timeout) {
+                println!("No video data for {:.1}s, stream stalled", idle.as_secs_f64());
+                shared.stalls_total.fetch_add(1, Ordering::Relaxed);
+                shared.events.emit(PlayerEvent::Stalled { idle });
+                if shared.is_playing() {
+                    shared.request_reconnect(ReconnectReason::Stalled);
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+        *self.watchdog_timer.lock().unwrap() = Some(timer);
+    }
+
+    pub(crate) fn transport(&self) -> Transport {
+        *self.transport.lock().unwrap()
+    }
+
+    /// Switch UDP sessions that receive nothing over to TCP.
+    fn setup_fallback_timer(self: &Arc<Self>) {
+        let Some(fallback_after) = self.config.tcp_fallback() else { return };
+        if !matches!(self.config.transport(), Transport::UdpUnicast | Transport::UdpMulticast) {
+            return;
+        }
+        let shared: Weak<Shared> = Arc::downgrade(self);
+
+        let timer = self.dispatcher.schedule(WATCHDOG_INTERVAL, move || {
+            let Some(shared) = shared.upgrade() else { return glib::ControlFlow::Break };
+            let from = shared.transport();
+            if !from.is_udp() || !shared.is_playing() {
+                return glib::ControlFlow::Continue;
+            }
+            if let Some(waited) = shared.fallback.check(fallback_after) {
+                let to = Transport::TcpInterleaved;
+                println!("No data over {} for {:.1}s, falling back to {}", from, waited.as_secs_f64(), to);
+                *shared.transport.lock().unwrap() = to;
+                shared.events.emit(PlayerEvent::TransportFallback { from, to, waited });
+                shared.request_reconnect(ReconnectReason::TransportFallback);
+            }
+            glib::ControlFlow::Continue
+        });
+        *self.fallback_timer.lock().unwrap() = Some(timer);
+    }
+}
+
+impl Drop for RtspPlayer {
+    fn drop(&mut self) {
+        // No reconnect may rebuild the pipeline while it is being torn down
+        self.supervisor.shutdown();
+        let _ = self.shared.finalize_recording();
+
+        if let Some(bus_watch) = self.shared.bus_watch.lock().unwrap().take() {
+            bus_watch.destroy();
+        }
+        if let Some(stats_timer) = self.shared.stats_timer.lock().unwrap().take() {
+            stats_timer.destroy();
+        }
+        if let Some(watchdog_timer) = self.shared.watchdog_timer.lock().unwrap().take() {
+            watchdog_timer.destroy();
+        }
+        if let Some(fallback_timer) = self.shared.fallback_timer.lock().unwrap().take() {
+            fallback_timer.destroy();
+        }
+        let _ = self.shared.pipeline().set_state(gst::State::Null);
+        self.shared.holds_decoder.store(false, Ordering::Relaxed);
+        self.shared.frames.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_player() {
+        let config = RtspPlayerConfig::builder("rtsp://127.0.0.1:8554/test.sdp")
+            .headless(true)
+            .build()
+            .unwrap();
+        let player = RtspPlayer::with_config(config).expect("Should create a headless player");
+        assert_eq!(player.video_sink(), &VideoSinkKind::Fake);
+        assert!(!player.is_playing());
+        assert!(player.set_window_handle(0).is_err(), "fakesink cannot be embedded");
+    }
+}
@@ -0,0 +1,250 @@
+use crate::player::Shared;
+use crate::{PlayerError, PlayerEvent};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::Weak;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How and how often the player tries to get a broken stream back.
+///
+/// The delay before attempt `n` is `initial_delay * multiplier^(n-1)`, capped
+/// at `max_delay` and then spread by up to `jitter` (a fraction) in either
+/// direction so that many players losing the same server don't retry in lockstep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    /// `None` retries forever.
+    pub max_attempts: Option<u32>,
+    /// Rebuild the pipeline from scratch on every attempt instead of only
+    /// when restarting the existing one did not help.
+    pub always_rebuild: bool,
+    /// Treat end-of-stream as a connection loss. Live cameras never end on
+    /// their own, so EOS usually means the server dropped the session.
+    pub reconnect_on_eos: bool,
+}
+
+impl ReconnectPolicy {
+    /// Keep retrying forever with the default backoff.
+    pub fn infinite() -> Self {
+        ReconnectPolicy { max_attempts: None, ..Default::default() }
+    }
+
+    /// Disable reconnection, errors stop playback.
+    pub fn disabled() -> Self {
+        ReconnectPolicy { max_attempts: Some(0), ..Default::default() }
+    }
+
+    /// Delay before the given 1-based attempt. `jitter_sample` is uniform in `[0, 1)`.
+    pub fn delay_for(&self, attempt: u32, jitter_sample: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(63) as i32;
+        let base = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let capped = base.min(self.max_delay.as_secs_f64());
+        let spread = capped * self.jitter * (2.0 * jitter_sample - 1.0);
+        Duration::from_secs_f64((capped + spread).clamp(0.0, self.max_delay.as_secs_f64()))
+    }
+
+    pub(crate) fn validate(&self) -> std::result::Result<(), PlayerError> {
+        if self.multiplier < 1.0 {
+            return Err(PlayerError::ConfigError("Reconnect multiplier must be at least 1.0".into()));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(PlayerError::ConfigError("Reconnect jitter must be between 0.0 and 1.0".into()));
+        }
+        if self.initial_delay > self.max_delay {
+            return Err(PlayerError::ConfigError("Initial reconnect delay must not exceed the maximum delay".into()));
+        }
+        Ok(())
+    }
+
+    fn exhausted(&self, attempt: u32) -> bool {
+        self.max_attempts.map(|max| attempt > max).unwrap_or(false)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: Some(5),
+            always_rebuild: false,
+            reconnect_on_eos: false,
+        }
+    }
+}
+
+/// Why a reconnect was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectReason {
+    Error,
+    EndOfStream,
+    /// No frames arrived within the configured stall timeout.
+    Stalled,
+    /// UDP delivered nothing, retrying over TCP.
+    TransportFallback,
+}
+
+pub(crate) enum SupervisorCommand {
+    Reconnect(ReconnectReason),
+    Shutdown,
+}
+
+/// Runs reconnection attempts on its own thread so the bus thread never sleeps.
+///
+/// Requests arriving while an attempt is pending are folded into it.
+#[derive(Debug)]
+pub(crate) struct ReconnectSupervisor {
+    commands: Sender<SupervisorCommand>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReconnectSupervisor {
+    pub(crate) fn spawn(
+        shared: Weak<Shared>,
+        commands: Sender<SupervisorCommand>,
+        receiver: Receiver<SupervisorCommand>,
+    ) -> std::result::Result<Self, PlayerError> {
+        let thread = std::thread::Builder::new()
+            .name("rtsp-player-reconnect".into())
+            .spawn(move || supervise(shared, receiver))
+            .map_err(|err| PlayerError::InitError(format!("Failed to start reconnect thread: {}", err)))?;
+
+        Ok(ReconnectSupervisor { commands, thread: Some(thread) })
+    }
+
+    pub(crate) fn shutdown(&mut self) {
+        let _ = self.commands.send(SupervisorCommand::Shutdown);
+        if let Some(thread) = self.thread.take()
+            && thread.thread().id() != std::thread::current().id()
+        {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ReconnectSupervisor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn supervise(shared: Weak<Shared>, receiver: Receiver<SupervisorCommand>) {
+    let mut rng = seed();
+
+    while let Ok(command) = receiver.recv() {
+        let reason = match command {
+            SupervisorCommand::Reconnect(reason) => reason,
+            SupervisorCommand::Shutdown => return,
+        };
+        let Some(player) = shared.upgrade() else { return };
+        if !player.is_playing() {
+            // Stopped by the user in the meantime
+            continue;
+        }
+
+        let policy = player.config.reconnect_policy().clone();
+        let attempt = {
+            let mut attempts = player.reconnect_attempts.lock().unwrap();
+            *attempts += 1;
+            *attempts
+        };
+
+        if policy.exhausted(attempt) {
+            println!("Max reconnection attempts reached, giving up");
+            player.give_up(attempt - 1);
+            continue;
+        }
+
+        let delay = policy.delay_for(attempt, next_sample(&mut rng));
+        println!("Reconnecting in {:.1}s (attempt {}, reason {:?})", delay.as_secs_f64(), attempt, reason);
+        player.reconnects_total.fetch_add(1, Ordering::Relaxed);
+        player.events.emit(PlayerEvent::Reconnecting {
+            attempt,
+            max_attempts: policy.max_attempts,
+            delay,
+            reason,
+        });
+        player.halt();
+        drop(player);
+
+        // Wait out the backoff, swallowing duplicate requests from the dying pipeline
+        let deadline = Instant::now() + delay;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok(SupervisorCommand::Reconnect(_)) => continue,
+                Ok(SupervisorCommand::Shutdown) | Err(RecvTimeoutError::Disconnected) => return,
+                Err(RecvTimeoutError::Timeout) => break,
+            }
+        }
+
+        let Some(player) = shared.upgrade() else { return };
+        if !player.is_playing() {
+            continue;
+        }
+
+        let rebuild = policy.always_rebuild || attempt > 1;
+        if let Err(err) = player.restart(rebuild) {
+            println!("Reconnect attempt {} failed: {}", attempt, err);
+            player.events.emit(PlayerEvent::Error(err));
+            player.request_reconnect(reason);
+        }
+    }
+}
+
+fn seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    // xorshift must not start at zero
+    nanos | 1
+}
+
+/// xorshift64*, plenty for spreading retries.
+fn next_sample(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    let value = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let policy = ReconnectPolicy { jitter: 0.0, ..Default::default() };
+        assert_eq!(policy.delay_for(1, 0.5), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(2, 0.5), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(4, 0.5), Duration::from_secs(8));
+        assert_eq!(policy.delay_for(20, 0.5), Duration::from_secs(30));
+        assert_eq!(policy.delay_for(u32::MAX, 0.5), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_jitter_stays_in_range() {
+        let policy = ReconnectPolicy::default();
+        let mut rng = seed();
+        for _ in 0..1000 {
+            let delay = policy.delay_for(3, next_sample(&mut rng));
+            assert!(delay >= Duration::from_secs_f64(3.2) && delay <= Duration::from_secs_f64(4.8));
+        }
+    }
+
+    #[test]
+    fn test_attempt_limits() {
+        assert!(!ReconnectPolicy::infinite().exhausted(u32::MAX));
+        assert!(ReconnectPolicy::disabled().exhausted(1));
+        assert!(!ReconnectPolicy::default().exhausted(5));
+        assert!(ReconnectPolicy::default().exhausted(6));
+    }
+}
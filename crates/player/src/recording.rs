@@ -0,0 +1,434 @@
+use crate::pipeline::require_element;
+use crate::segments::{enforce_retention, SegmentOptions};
+use crate::PlayerError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long `stop_recording` waits for the muxer to write out the file.
+const FINALIZE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// MP4 files are fragmented so that everything up to the last fragment stays
+/// playable if the process dies before the file is finalized.
+const MP4_FRAGMENT_DURATION_MS: u32 = 1000;
+
+/// Container written by a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingFormat {
+    Mp4,
+    Matroska,
+}
+
+impl RecordingFormat {
+    /// Pick the container from the file extension: `.mkv` is Matroska, anything else MP4.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("mkv") => RecordingFormat::Matroska,
+            _ => RecordingFormat::Mp4,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RecordingFormat::Mp4 => "mp4",
+            RecordingFormat::Matroska => "mkv",
+        }
+    }
+
+    fn muxer(&self) -> &'static str {
+        match self {
+            RecordingFormat::Mp4 => "mp4mux",
+            RecordingFormat::Matroska => "matroskamux",
+        }
+    }
+}
+
+/// What the user asked to record, kept so a recording interrupted by a
+/// reconnect can be started again once the stream is back.
+#[derive(Debug, Clone)]
+pub(crate) enum RecordingRequest {
+    /// A single file. The interrupted file is complete, so each reconnect
+    /// continues in a new one with `-<part>` added to the name.
+    File { path: PathBuf, format: RecordingFormat, part: u32 },
+    /// Segments, numbered on from `start_index` so a resumed recording
+    /// never reuses an index.
+    Segmented { options: SegmentOptions, start_index: u32 },
+}
+
+impl RecordingRequest {
+    pub(crate) fn file(path: &Path, format: RecordingFormat) -> Self {
+        RecordingRequest::File { path: path.to_path_buf(), format, part: 0 }
+    }
+
+    /// The file, or the directory of a segmented recording.
+    pub(crate) fn path(&self) -> PathBuf {
+        match self {
+            RecordingRequest::File { path, part, .. } => part_path(path, *part),
+            RecordingRequest::Segmented { options, .. } => options.directory.clone(),
+        }
+    }
+
+    pub(crate) fn start(self, pipeline: &gst::Pipeline) -> std::result::Result<Recording, PlayerError> {
+        match &self {
+            RecordingRequest::File { format, .. } => Recording::start(pipeline, &self.path(), *format, self.clone()),
+            RecordingRequest::Segmented { options, start_index } => {
+                Recording::start_segmented(pipeline, options.clone(), *start_index, self.clone())
+            }
+        }
+    }
+
+    /// The request that continues this recording after a reconnect, with
+    /// `next_segment` as the index of the next segment to open.
+    fn next(&self, next_segment: u32) -> Self {
+        match self {
+            RecordingRequest::File { path, format, part } => {
+                RecordingRequest::File { path: path.clone(), format: *format, part: part + 1 }
+            }
+            RecordingRequest::Segmented { options, .. } => {
+                RecordingRequest::Segmented { options: options.clone(), start_index: next_segment }
+            }
+        }
+    }
+}
+
+/// `cam.mp4` for the first part, then `cam-1.mp4`, `cam-2.mp4`, ...
+fn part_path(path: &Path, part: u32) -> PathBuf {
+    if part == 0 {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, part, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, part),
+    };
+    path.with_file_name(name)
+}
+
+/// A recording branch hanging off the pipeline's `tee`:
+///
+/// `tee ! queue ! <parse> ! <mux> ! filesink` for a single file, or
+/// `tee ! queue ! <parse> ! splitmuxsink` for segmented recording.
+///
+/// The branch is added and removed while the pipeline keeps playing, the
+/// display branch is never interrupted.
+#[derive(Debug)]
+pub(crate) struct Recording {
+    /// The file, or the directory of a segmented recording.
+    path: PathBuf,
+    request: RecordingRequest,
+    segments: Option<SegmentOptions>,
+    current_segment: Option<PathBuf>,
+    /// Index splitmuxsink will give the next segment.
+    next_segment: Arc<AtomicU32>,
+    bin: gst::Bin,
+    tee_pad: gst::Pad,
+    eos_received: Receiver<()>,
+}
+
+impl Recording {
+    fn start(
+        pipeline: &gst::Pipeline,
+        path: &Path,
+        format: RecordingFormat,
+        request: RecordingRequest,
+    ) -> std::result::Result<Self, PlayerError> {
+        require_element("filesink")?;
+        let location = path
+            .to_str()
+            .ok_or_else(|| PlayerError::RecordingError("Recording path must be valid UTF-8".into()))?;
+
+        let mux = make_muxer(format)?;
+        let filesink = gst::ElementFactory::make("filesink")
+            .name("recording-sink")
+            .property("location", location)
+            .property("async", false)
+            .build()?;
+        let eos_pad = filesink.static_pad("sink").expect("filesink has a sink pad");
+
+        let (bin, tee_pad, eos_received) = attach_branch(pipeline, &[mux, filesink], &eos_pad)?;
+        Ok(Recording {
+            path: path.to_path_buf(),
+            request,
+            segments: None,
+            current_segment: None,
+            next_segment: Arc::default(),
+            bin,
+            tee_pad,
+            eos_received,
+        })
+    }
+
+    /// Record into rotating segments, see [`SegmentOptions`].
+    fn start_segmented(
+        pipeline: &gst::Pipeline,
+        options: SegmentOptions,
+        start_index: u32,
+        request: RecordingRequest,
+    ) -> std::result::Result<Self, PlayerError> {
+        options.validate()?;
+        require_element("splitmuxsink")?;
+
+        // splitmuxsink also sends EOS to its sink between segments; `finalize`
+        // discards those before sending the final one
+        let filesink = gst::ElementFactory::make("filesink")
+            .name("recording-sink")
+            .property("async", false)
+            .build()?;
+        let eos_pad = filesink.static_pad("sink").expect("filesink has a sink pad");
+
+        let splitmux = gst::ElementFactory::make("splitmuxsink")
+            .name("recording-splitmux")
+            .property("muxer", make_muxer(options.format)?)
+            .property("sink", &filesink)
+            .property("max-size-time", options.max_duration.unwrap_or_default().as_nanos() as u64)
+            .property("max-size-bytes", options.max_bytes.unwrap_or(0))
+            .property("start-index", i32::try_from(start_index).unwrap_or(i32::MAX))
+            .build()?;
+
+        let naming = options.clone();
+        let next_segment = Arc::new(AtomicU32::new(start_index));
+        let opened = Arc::clone(&next_segment);
+        splitmux.connect("format-location", false, move |args| {
+            let index = args[1].get::<u32>().unwrap_or(0);
+            opened.store(index.saturating_add(1), Ordering::Relaxed);
+            let path = naming.segment_path(index);
+            Some(path.to_string_lossy().to_string().to_value())
+        });
+
+        let (bin, tee_pad, eos_received) = attach_branch(pipeline, &[splitmux], &eos_pad)?;
+        enforce_retention(&options.directory, options.format.extension(), &options.retention, None);
+
+        Ok(Recording {
+            path: options.directory.clone(),
+            request,
+            segments: Some(options),
+            current_segment: None,
+            next_segment,
+            bin,
+            tee_pad,
+            eos_received,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The request that continues this recording after a reconnect.
+    pub(crate) fn next_request(&self) -> RecordingRequest {
+        self.request.next(self.next_segment.load(Ordering::Relaxed))
+    }
+
+    /// Whether `object` is one of the branch's elements.
+    pub(crate) fn contains(&self, object: &gst::Object) -> bool {
+        object.has_as_ancestor(&self.bin)
+    }
+
+    /// Track the segment splitmuxsink switched to and delete segments beyond
+    /// the retention policy. Returns the closed segment, if `structure` was a
+    /// `splitmuxsink-fragment-closed` message.
+    pub(crate) fn handle_segment_message(&mut self, structure: &gst::StructureRef) -> Option<PathBuf> {
+        let options = self.segments.as_ref()?;
+        let location = PathBuf::from(structure.get::<&str>("location").ok()?);
+
+        match structure.name().as_str() {
+            "splitmuxsink-fragment-opened" => {
+                self.current_segment = Some(location);
+                None
+            }
+            "splitmuxsink-fragment-closed" => {
+                let deleted = enforce_retention(
+                    &options.directory,
+                    options.format.extension(),
+                    &options.retention,
+                    self.current_segment.as_deref(),
+                );
+                for path in deleted {
+                    println!("Deleted old segment {}", path.display());
+                }
+                Some(location)
+            }
+            _ => None,
+        }
+    }
+
+    /// Detach the branch, push EOS through it so the muxer writes its index,
+    /// and remove it once the file is complete.
+    pub(crate) fn finalize(self, pipeline: &gst::Pipeline) -> std::result::Result<PathBuf, PlayerError> {
+        self.detach();
+        self.wait_and_remove(pipeline)
+    }
+
+    /// Like `finalize`, but the pipeline may be shut down right away: the
+    /// branch keeps its own state and the muxer is waited for on a thread of
+    /// its own, so the bus thread never blocks on it.
+    pub(crate) fn finalize_in_background<F>(self, pipeline: &gst::Pipeline, on_done: F)
+    where
+        F: FnOnce(std::result::Result<PathBuf, PlayerError>) + Send + 'static,
+    {
+        let _ = self.bin.set_locked_state(true);
+        self.detach();
+
+        let pipeline = pipeline.clone();
+        let spawned = std::thread::Builder::new()
+            .name("rtsp-player-recording".into())
+            .spawn(move || on_done(self.wait_and_remove(&pipeline)));
+        if let Err(err) = spawned {
+            println!("Failed to start recording finalizer thread: {}", err);
+        }
+    }
+
+    /// Unlink the branch from the tee once no buffer is passing and send EOS
+    /// into it so the muxer writes its index.
+    fn detach(&self) {
+        while self.eos_received.try_recv().is_ok() {}
+
+        let ghost = self.bin.static_pad("sink").expect("recording bin has a sink pad");
+        self.tee_pad.add_probe(gst::PadProbeType::IDLE, move |tee_pad, _| {
+            let _ = tee_pad.unlink(&ghost);
+            ghost.send_event(gst::event::Eos::new());
+            gst::PadProbeReturn::Remove
+        });
+    }
+
+    fn wait_and_remove(self, pipeline: &gst::Pipeline) -> std::result::Result<PathBuf, PlayerError> {
+        let finished = self.eos_received.recv_timeout(FINALIZE_TIMEOUT).is_ok();
+        self.remove(pipeline);
+        if finished {
+            Ok(self.path)
+        } else {
+            Err(PlayerError::RecordingError(format!("Timed out finalizing {}", self.path.display())))
+        }
+    }
+
+    /// Remove the branch without finalizing, for branches that already failed.
+    pub(crate) fn abort(self, pipeline: &gst::Pipeline) -> PathBuf {
+        if let Some(ghost) = self.bin.static_pad("sink") {
+            let _ = self.tee_pad.unlink(&ghost);
+        }
+        self.remove(pipeline);
+        self.path
+    }
+
+    fn remove(&self, pipeline: &gst::Pipeline) {
+        let _ = self.bin.set_locked_state(false);
+        let _ = self.bin.set_state(gst::State::Null);
+        let _ = pipeline.remove(&self.bin);
+        if let Some(tee) = self.tee_pad.parent_element() {
+            tee.release_request_pad(&self.tee_pad);
+        }
+    }
+}
+
+fn make_muxer(format: RecordingFormat) -> std::result::Result<gst::Element, PlayerError> {
+    require_element(format.muxer())?;
+    let mut mux_builder = gst::ElementFactory::make(format.muxer()).name("recording-mux");
+    if format == RecordingFormat::Mp4 {
+        mux_builder = mux_builder.property("fragment-duration", MP4_FRAGMENT_DURATION_MS);
+    }
+    Ok(mux_builder.build()?)
+}
+
+/// Wrap `queue ! <parse> ! <tail>` in a bin and link it to a new tee pad.
+/// `eos_pad` is where the branch's EOS shows up once the file is written.
+fn attach_branch(
+    pipeline: &gst::Pipeline,
+    tail: &[gst::Element],
+    eos_pad: &gst::Pad,
+) -> std::result::Result<(gst::Bin, gst::Pad, Receiver<()>), PlayerError> {
+    let tee = pipeline
+        .by_name("tee")
+        .ok_or_else(|| PlayerError::RecordingError("Pipeline has no tee to record from".into()))?;
+    let caps = tee
+        .static_pad("sink")
+        .and_then(|pad| pad.current_caps())
+        .ok_or_else(|| PlayerError::RecordingError("The stream has not started yet".into()))?;
+    let parser = match caps.structure(0).map(|s| s.name().as_str()) {
+        Some("video/x-h264") => "h264parse",
+        Some("video/x-h265") => "h265parse",
+        _ => return Err(PlayerError::RecordingError("Recording requires an H.264 or H.265 stream".into())),
+    };
+    require_element("queue")?;
+    require_element(parser)?;
+
+    let bin = gst::Bin::with_name("recording");
+    let queue = gst::ElementFactory::make("queue").name("recording-queue").build()?;
+    let parse = gst::ElementFactory::make(parser).name("recording-parse").build()?;
+
+    bin.add_many([&queue, &parse])?;
+    bin.add_many(tail)?;
+    gst::Element::link_many([&queue, &parse].into_iter().chain(tail))?;
+
+    let queue_sink = queue.static_pad("sink").expect("queue has a sink pad");
+    // A file must start with a keyframe, drop everything before the first one
+    queue_sink.add_probe(gst::PadProbeType::BUFFER, |_, info| match &info.data {
+        Some(gst::PadProbeData::Buffer(buffer)) if buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) => {
+            gst::PadProbeReturn::Drop
+        }
+        _ => gst::PadProbeReturn::Remove,
+    });
+    let ghost = gst::GhostPad::with_target(&queue_sink)?;
+    bin.add_pad(&ghost)?;
+
+    let (eos_sender, eos_received) = channel();
+    eos_pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |_, info| {
+        if let Some(gst::PadProbeData::Event(event)) = &info.data
+            && event.type_() == gst::EventType::Eos
+        {
+            let _ = eos_sender.send(());
+        }
+        gst::PadProbeReturn::Ok
+    });
+
+    pipeline.add(&bin)?;
+    match link_to_tee(&bin, &tee, &ghost) {
+        Ok(tee_pad) => Ok((bin, tee_pad, eos_received)),
+        Err(err) => {
+            let _ = bin.set_state(gst::State::Null);
+            let _ = pipeline.remove(&bin);
+            Err(err)
+        }
+    }
+}
+
+fn link_to_tee(bin: &gst::Bin, tee: &gst::Element, ghost: &gst::GhostPad) -> std::result::Result<gst::Pad, PlayerError> {
+    bin.sync_state_with_parent()?;
+    let tee_pad = tee
+        .request_pad_simple("src_%u")
+        .ok_or_else(|| PlayerError::RecordingError("Could not get a tee pad".into()))?;
+    if let Err(err) = tee_pad.link(ghost) {
+        tee.release_request_pad(&tee_pad);
+        return Err(PlayerError::RecordingError(format!("Could not link the recording branch: {:?}", err)));
+    }
+    Ok(tee_pad)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_extension() {
+        assert_eq!(RecordingFormat::from_path(Path::new("cam.mkv")), RecordingFormat::Matroska);
+        assert_eq!(RecordingFormat::from_path(Path::new("cam.MKV")), RecordingFormat::Matroska);
+        assert_eq!(RecordingFormat::from_path(Path::new("cam.mp4")), RecordingFormat::Mp4);
+        assert_eq!(RecordingFormat::from_path(Path::new("cam")), RecordingFormat::Mp4);
+    }
+
+    #[test]
+    fn test_resumed_file_gets_new_name() {
+        let request = RecordingRequest::file(Path::new("/rec/cam.mp4"), RecordingFormat::Mp4);
+        assert_eq!(request.path(), PathBuf::from("/rec/cam.mp4"));
+        assert_eq!(request.next(0).path(), PathBuf::from("/rec/cam-1.mp4"));
+        assert_eq!(request.next(0).next(0).path(), PathBuf::from("/rec/cam-2.mp4"));
+        assert_eq!(part_path(Path::new("cam"), 3), PathBuf::from("cam-3"));
+
+        let options = SegmentOptions::new("/rec", "cam");
+        let request = RecordingRequest::Segmented { options, start_index: 0 };
+        assert!(matches!(request.next(7), RecordingRequest::Segmented { start_index: 7, .. }));
+    }
+}
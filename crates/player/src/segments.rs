@@ -0,0 +1,190 @@
+use crate::{PlayerError, RecordingFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default file name of a segment, e.g. `front-door_20250101-120000_00042.mp4`.
+pub const DEFAULT_SEGMENT_TEMPLATE: &str = "{camera}_{timestamp}_{index}.{ext}";
+
+/// Which old segments to delete once a new one has been written.
+///
+/// Every file with the recording's extension in the segment directory counts,
+/// so give each camera its own directory. `None` disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    pub max_total_bytes: Option<u64>,
+    pub max_files: Option<usize>,
+}
+
+/// Continuous recording split into files of bounded length or size.
+///
+/// Files are cut on keyframes once `max_duration` or `max_bytes` is reached.
+/// `template` names each file and understands `{camera}`, `{timestamp}`
+/// (local wall-clock time the segment was opened), `{index}` and `{ext}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SegmentOptions {
+    pub directory: PathBuf,
+    pub camera: String,
+    pub template: String,
+    pub format: RecordingFormat,
+    pub max_duration: Option<Duration>,
+    pub max_bytes: Option<u64>,
+    pub retention: RetentionPolicy,
+}
+
+impl SegmentOptions {
+    /// Ten minute MP4 segments with the default template and no retention limit.
+    pub fn new(directory: impl Into<PathBuf>, camera: &str) -> Self {
+        SegmentOptions {
+            directory: directory.into(),
+            camera: camera.to_string(),
+            template: DEFAULT_SEGMENT_TEMPLATE.to_string(),
+            format: RecordingFormat::Mp4,
+            max_duration: Some(Duration::from_secs(600)),
+            max_bytes: None,
+            retention: RetentionPolicy::default(),
+        }
+    }
+
+    pub(crate) fn validate(&self) -> std::result::Result<(), PlayerError> {
+        let invalid = |msg: &str| Err(PlayerError::RecordingError(msg.into()));
+
+        if self.max_duration.is_none_or(|d| d.is_zero()) && self.max_bytes.is_none_or(|b| b == 0) {
+            return invalid("Segments need a maximum duration or size");
+        }
+        if !self.template.contains("{index}") && !self.template.contains("{timestamp}") {
+            return invalid("Segment template must contain {index} or {timestamp}");
+        }
+        if self.template.contains('/') || self.template.contains('\\') {
+            return invalid("Segment template must be a file name, set the directory separately");
+        }
+        fs::create_dir_all(&self.directory)
+            .map_err(|err| PlayerError::RecordingError(format!("Cannot create {}: {}", self.directory.display(), err)))
+    }
+
+    /// Path of the segment with the given index, stamped with the current time.
+    pub(crate) fn segment_path(&self, index: u32) -> PathBuf {
+        let name = expand_template(&self.template, &self.camera, &local_timestamp(), index, self.format.extension());
+        self.directory.join(name)
+    }
+}
+
+fn expand_template(template: &str, camera: &str, timestamp: &str, index: u32, ext: &str) -> String {
+    template
+        .replace("{camera}", &sanitize(camera))
+        .replace("{timestamp}", timestamp)
+        .replace("{index}", &format!("{:05}", index))
+        .replace("{ext}", ext)
+}
+
+/// Keep camera names from escaping the directory or upsetting Windows.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn local_timestamp() -> String {
+    glib::DateTime::now_local()
+        .and_then(|now| now.format("%Y%m%d-%H%M%S"))
+        .map(|stamp| stamp.to_string())
+        .unwrap_or_else(|_| {
+            let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            secs.to_string()
+        })
+}
+
+#[derive(Debug, Clone)]
+struct SegmentFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Delete the oldest segments in `directory` beyond the policy's limits.
+/// `keep` (the segment being written) counts towards the limits but is never deleted.
+pub(crate) fn enforce_retention(directory: &Path, ext: &str, policy: &RetentionPolicy, keep: Option<&Path>) -> Vec<PathBuf> {
+    if policy.max_total_bytes.is_none() && policy.max_files.is_none() {
+        return Vec::new();
+    }
+    let Ok(entries) = fs::read_dir(directory) else { return Vec::new() };
+
+    let files = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e.eq_ignore_ascii_case(ext)))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| SegmentFile {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(UNIX_EPOCH),
+            })
+        })
+        .collect();
+
+    let mut deleted = Vec::new();
+    for path in expired(files, policy, keep) {
+        match fs::remove_file(&path) {
+            Ok(()) => deleted.push(path),
+            Err(err) => println!("Failed to delete old segment {}: {}", path.display(), err),
+        }
+    }
+    deleted
+}
+
+/// Oldest files to drop so that the rest fits the policy.
+fn expired(mut files: Vec<SegmentFile>, policy: &RetentionPolicy, keep: Option<&Path>) -> Vec<PathBuf> {
+    files.sort_by_key(|file| file.modified);
+    let mut total: u64 = files.iter().map(|file| file.size).sum();
+    let mut count = files.len();
+
+    let mut expired = Vec::new();
+    for file in files {
+        let over_size = policy.max_total_bytes.is_some_and(|max| total > max);
+        let over_count = policy.max_files.is_some_and(|max| count > max);
+        if !over_size && !over_count {
+            break;
+        }
+        if keep == Some(file.path.as_path()) {
+            continue;
+        }
+        total -= file.size;
+        count -= 1;
+        expired.push(file.path);
+    }
+    expired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_expansion() {
+        let name = expand_template(DEFAULT_SEGMENT_TEMPLATE, "front door/1", "20250101-120000", 42, "mkv");
+        assert_eq!(name, "front_door_1_20250101-120000_00042.mkv");
+    }
+
+    #[test]
+    fn test_oldest_segments_expire_first() {
+        let file = |name: &str, size: u64, age: u64| SegmentFile {
+            path: PathBuf::from(name),
+            size,
+            modified: UNIX_EPOCH + Duration::from_secs(1000 - age),
+        };
+        let files = vec![file("b", 100, 20), file("a", 100, 30), file("c", 100, 10)];
+
+        let by_size = RetentionPolicy { max_total_bytes: Some(150), max_files: None };
+        assert_eq!(expired(files.clone(), &by_size, None), vec![PathBuf::from("a"), PathBuf::from("b")]);
+
+        let by_count = RetentionPolicy { max_total_bytes: None, max_files: Some(2) };
+        assert_eq!(expired(files.clone(), &by_count, None), vec![PathBuf::from("a")]);
+
+        assert!(expired(files.clone(), &RetentionPolicy::default(), None).is_empty());
+
+        // The segment being written counts towards the quota but stays
+        let by_size = RetentionPolicy { max_total_bytes: Some(250), max_files: None };
+        assert_eq!(expired(files.clone(), &by_size, Some(Path::new("c"))), vec![PathBuf::from("a")]);
+        assert_eq!(expired(files, &by_size, Some(Path::new("a"))), vec![PathBuf::from("b")]);
+    }
+}
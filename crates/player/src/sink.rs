@@ -0,0 +1,137 @@
+use crate::{GstErrorInfo, PlayerError};
+use gstreamer as gst;
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+/// The video sink element terminating the playback pipeline.
+///
+/// `Auto` is resolved to a concrete sink by [`VideoSinkKind::resolve`] based on
+/// the platform, the display server in use and which GStreamer plugins are
+/// installed. Any other variant is used as-is.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum VideoSinkKind {
+    #[default]
+    Auto,
+    D3d11,
+    XvImage,
+    GlImage,
+    Wayland,
+    Fake,
+    App,
+    /// Any other element factory, e.g. `autovideosink` or `kmssink`.
+    Custom(String),
+}
+
+impl VideoSinkKind {
+    /// Name of the GStreamer element factory for this sink, `None` for `Auto`.
+    pub fn factory_name(&self) -> Option<&str> {
+        match self {
+            VideoSinkKind::Auto => None,
+            VideoSinkKind::D3d11 => Some("d3d11videosink"),
+            VideoSinkKind::XvImage => Some("xvimagesink"),
+            VideoSinkKind::GlImage => Some("glimagesink"),
+            VideoSinkKind::Wayland => Some("waylandsink"),
+            VideoSinkKind::Fake => Some("fakesink"),
+            VideoSinkKind::App => Some("appsink"),
+            VideoSinkKind::Custom(name) => Some(name.as_str()),
+        }
+    }
+
+    /// Whether the sink renders nothing on screen.
+    pub fn is_headless(&self) -> bool {
+        matches!(self, VideoSinkKind::Fake | VideoSinkKind::App)
+    }
+
+    /// Sinks to try, in order of preference, when `Auto` is requested.
+    fn candidates(headless: bool) -> Vec<VideoSinkKind> {
+        if headless {
+            return vec![VideoSinkKind::Fake];
+        }
+
+        if cfg!(windows) {
+            vec![VideoSinkKind::D3d11, VideoSinkKind::GlImage]
+        } else if env::var_os("WAYLAND_DISPLAY").is_some() {
+            vec![VideoSinkKind::Wayland, VideoSinkKind::GlImage, VideoSinkKind::XvImage]
+        } else if env::var_os("DISPLAY").is_some() {
+            vec![VideoSinkKind::XvImage, VideoSinkKind::GlImage]
+        } else {
+            // No display server available, nothing could be rendered anyway
+            vec![VideoSinkKind::Fake]
+        }
+    }
+
+    /// Resolve this request to a concrete sink whose plugin is installed.
+    ///
+    /// `Auto` walks the platform candidates and falls back to `fakesink` when
+    /// `headless` is set or no display sink is available. Explicit choices are
+    /// only checked for availability.
+    pub fn resolve(&self, headless: bool) -> Result<VideoSinkKind, PlayerError> {
+        match self {
+            VideoSinkKind::Auto => VideoSinkKind::candidates(headless)
+                .into_iter()
+                .find(|kind| kind.is_available())
+                .ok_or_else(|| PlayerError::SinkError(GstErrorInfo::local("No usable video sink found for this platform"))),
+            kind if kind.is_available() => Ok(kind.clone()),
+            kind => Err(PlayerError::missing_element(&kind.to_string())),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.factory_name()
+            .map(|name| gst::ElementFactory::find(name).is_some())
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for VideoSinkKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.factory_name().unwrap_or("auto"))
+    }
+}
+
+impl FromStr for VideoSinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let kind = match s.trim() {
+            "" => return Err("Empty video sink name".into()),
+            "auto" => VideoSinkKind::Auto,
+            "d3d11videosink" | "d3d11" => VideoSinkKind::D3d11,
+            "xvimagesink" | "xv" => VideoSinkKind::XvImage,
+            "glimagesink" | "gl" => VideoSinkKind::GlImage,
+            "waylandsink" | "wayland" => VideoSinkKind::Wayland,
+            "fakesink" | "fake" => VideoSinkKind::Fake,
+            "appsink" | "app" => VideoSinkKind::App,
+            other => VideoSinkKind::Custom(other.to_string()),
+        };
+        Ok(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sink_names() {
+        assert_eq!("auto".parse::<VideoSinkKind>(), Ok(VideoSinkKind::Auto));
+        assert_eq!("d3d11videosink".parse::<VideoSinkKind>(), Ok(VideoSinkKind::D3d11));
+        assert_eq!("wayland".parse::<VideoSinkKind>(), Ok(VideoSinkKind::Wayland));
+        assert_eq!(
+            "kmssink".parse::<VideoSinkKind>(),
+            Ok(VideoSinkKind::Custom("kmssink".into()))
+        );
+        assert!("".parse::<VideoSinkKind>().is_err());
+    }
+
+    #[test]
+    fn test_factory_round_trip() {
+        for kind in [VideoSinkKind::XvImage, VideoSinkKind::Fake, VideoSinkKind::App] {
+            let name = kind.factory_name().unwrap();
+            assert_eq!(name.parse::<VideoSinkKind>(), Ok(kind));
+        }
+        assert!(VideoSinkKind::Fake.is_headless());
+        assert!(!VideoSinkKind::GlImage.is_headless());
+    }
+}
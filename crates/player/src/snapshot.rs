@@ -0,0 +1,157 @@
+use crate::PlayerError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video as gst_video;
+use std::path::Path;
+
+/// Upper bound for converting or encoding a single frame.
+const CONVERT_TIMEOUT: gst::ClockTime = gst::ClockTime::from_seconds(5);
+
+/// Pixel layout of raw frames handed to the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelFormat {
+    Rgb,
+    #[default]
+    Rgba,
+    Bgr,
+    Bgra,
+    Gray8,
+}
+
+impl PixelFormat {
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            PixelFormat::Rgb | PixelFormat::Bgr => 3,
+            PixelFormat::Rgba | PixelFormat::Bgra => 4,
+            PixelFormat::Gray8 => 1,
+        }
+    }
+
+    pub(crate) fn video_format(&self) -> gst_video::VideoFormat {
+        match self {
+            PixelFormat::Rgb => gst_video::VideoFormat::Rgb,
+            PixelFormat::Rgba => gst_video::VideoFormat::Rgba,
+            PixelFormat::Bgr => gst_video::VideoFormat::Bgr,
+            PixelFormat::Bgra => gst_video::VideoFormat::Bgra,
+            PixelFormat::Gray8 => gst_video::VideoFormat::Gray8,
+        }
+    }
+}
+
+/// Encoded image formats for [`crate::RtspPlayer::snapshot_to_file`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    /// `.jpg` and `.jpeg` are JPEG, anything else PNG.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("jpg") || ext.eq_ignore_ascii_case("jpeg") => ImageFormat::Jpeg,
+            _ => ImageFormat::Png,
+        }
+    }
+
+    fn caps(&self) -> gst::Caps {
+        match self {
+            ImageFormat::Png => gst::Caps::builder("image/png").build(),
+            ImageFormat::Jpeg => gst::Caps::builder("image/jpeg").build(),
+        }
+    }
+}
+
+/// A decoded frame with tightly packed rows (`width * bytes_per_pixel` bytes each).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub data: Vec<u8>,
+}
+
+/// Convert the frame the video sink rendered last.
+pub(crate) fn capture(pipeline: &gst::Pipeline, format: PixelFormat) -> std::result::Result<Snapshot, PlayerError> {
+    let caps = gst_video::VideoCapsBuilder::new()
+        .format(format.video_format())
+        .build();
+    let sample = convert(pipeline, &caps)?;
+
+    let caps = sample.caps().ok_or_else(|| snapshot_error("Converted frame has no caps"))?;
+    let info = gst_video::VideoInfo::from_caps(caps).map_err(|err| snapshot_error(&err.to_string()))?;
+    let buffer = sample.buffer().ok_or_else(|| snapshot_error("Converted frame has no data"))?;
+    let map = buffer.map_readable().map_err(|err| snapshot_error(&err.to_string()))?;
+
+    let row_bytes = info.width() as usize * format.bytes_per_pixel();
+    let data = pack_rows(
+        map.as_slice(),
+        info.offset()[0],
+        info.stride()[0] as usize,
+        row_bytes,
+        info.height() as usize,
+    )
+    .ok_or_else(|| snapshot_error("Converted frame is smaller than its caps claim"))?;
+
+    Ok(Snapshot {
+        width: info.width(),
+        height: info.height(),
+        format,
+        data,
+    })
+}
+
+/// Encode the frame the video sink rendered last as PNG or JPEG.
+pub(crate) fn encode(pipeline: &gst::Pipeline, format: ImageFormat) -> std::result::Result<Vec<u8>, PlayerError> {
+    let sample = convert(pipeline, &format.caps())?;
+    let buffer = sample.buffer().ok_or_else(|| snapshot_error("Encoded image has no data"))?;
+    let map = buffer.map_readable().map_err(|err| snapshot_error(&err.to_string()))?;
+    Ok(map.as_slice().to_vec())
+}
+
+fn convert(pipeline: &gst::Pipeline, caps: &gst::Caps) -> std::result::Result<gst::Sample, PlayerError> {
+    let sink = pipeline
+        .by_name("videosink")
+        .ok_or_else(|| snapshot_error("Could not find video sink"))?;
+    if sink.find_property("last-sample").is_none() {
+        return Err(snapshot_error("The video sink does not keep its last frame"));
+    }
+    let sample = sink
+        .property::<Option<gst::Sample>>("last-sample")
+        .ok_or_else(|| snapshot_error("No frame has been rendered yet"))?;
+
+    gst_video::convert_sample(&sample, caps, CONVERT_TIMEOUT).map_err(|err| snapshot_error(err.message()))
+}
+
+/// Copy `height` rows of `row_bytes` out of a buffer whose rows are `stride` apart.
+pub(crate) fn pack_rows(src: &[u8], offset: usize, stride: usize, row_bytes: usize, height: usize) -> Option<Vec<u8>> {
+    let mut data = Vec::with_capacity(row_bytes * height);
+    for row in 0..height {
+        let start = offset + row * stride;
+        data.extend_from_slice(src.get(start..start + row_bytes)?);
+    }
+    Some(data)
+}
+
+fn snapshot_error(msg: &str) -> PlayerError {
+    PlayerError::SnapshotError(msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding_is_stripped() {
+        // 2x2 RGB with rows padded to 8 bytes
+        let src = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12, 0, 0];
+        assert_eq!(pack_rows(&src, 0, 8, 6, 2), Some(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]));
+        assert_eq!(pack_rows(&src, 0, 8, 6, 3), None);
+    }
+
+    #[test]
+    fn test_image_format_from_extension() {
+        assert_eq!(ImageFormat::from_path(Path::new("still.JPG")), ImageFormat::Jpeg);
+        assert_eq!(ImageFormat::from_path(Path::new("still.png")), ImageFormat::Png);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::PlayerError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Periodic snapshot of playback health, emitted as `PlayerEvent::Stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamStats {
+    /// Current stream position, if the pipeline can report one.
+    pub position: Option<Duration>,
+    /// Stream duration, `None` for live streams.
+    pub duration: Option<Duration>,
+    /// Reconnection attempts since the stream last reached playing.
+    pub reconnect_attempts: u32,
+    /// Reconnection attempts since the player was created.
+    pub reconnects_total: u64,
+    /// Times the watchdog found the stream stalled.
+    pub stalls: u64,
+    /// Time since the player was created.
+    pub uptime: Duration,
+    /// Video payload arriving from the network, measured over the last interval.
+    pub bitrate_bps: u64,
+    /// Frames leaving the decoder per second over the last interval.
+    pub fps: f64,
+    /// Frames decoded since the player was created.
+    pub frames_decoded: u64,
+    /// Frames the video sink rendered, from its `stats` property.
+    pub frames_rendered: u64,
+    /// Frames the video sink dropped for being late.
+    pub frames_dropped: u64,
+    /// Buffers dropped according to QoS messages from the decoder and sinks.
+    pub qos_dropped: u64,
+    pub jitterbuffer: JitterbufferStats,
+    pub rtp: RtpStats,
+}
+
+/// Counters of the `rtpjitterbuffer` in front of the video depayloader.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JitterbufferStats {
+    pub pushed: u64,
+    pub lost: u64,
+    pub late: u64,
+    pub duplicates: u64,
+    pub avg_jitter: Duration,
+}
+
+/// Receiver statistics of the RTP session, the data reported back to the
+/// server in RTCP receiver reports. Summed over all media streams.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RtpStats {
+    pub packets_received: u64,
+    /// Cumulative packets lost, negative when duplicates outnumber losses.
+    pub packets_lost: i64,
+    /// Interarrival jitter of the worst stream.
+    pub jitter: Duration,
+    /// Bitrate as estimated by the RTP session.
+    pub bitrate_bps: u64,
+}
+
+/// Gathers the numbers behind [`StreamStats`] from pad probes, QoS messages
+/// and element statistics.
+#[derive(Debug)]
+pub(crate) struct StatsCollector {
+    frames: AtomicU64,
+    bytes: AtomicU64,
+    qos_dropped: Mutex<HashMap<String, u64>>,
+    last_collect: Mutex<(Instant, u64, u64)>,
+}
+
+impl Default for StatsCollector {
+    fn default() -> Self {
+        StatsCollector {
+            frames: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            qos_dropped: Mutex::new(HashMap::new()),
+            last_collect: Mutex::new((Instant::now(), 0, 0)),
+        }
+    }
+}
+
+impl StatsCollector {
+    /// Count bytes entering the jitterbuffer and frames leaving the decoder.
+    pub(crate) fn attach(self: &Arc<Self>, pipeline: &gst::Pipeline) -> std::result::Result<(), PlayerError> {
+        let network_pad = pipeline
+            .by_name("jitterbuffer")
+            .and_then(|jitterbuffer| jitterbuffer.static_pad("sink"))
+            .ok_or_else(|| PlayerError::InitError("Could not find the jitterbuffer".into()))?;
+        let decoded_pad = pipeline
+            .by_name("convert")
+            .and_then(|convert| convert.static_pad("sink"))
+            .ok_or_else(|| PlayerError::InitError("Could not find the decoded video pad".into()))?;
+
+        let collector = Arc::clone(self);
+        network_pad.add_probe(gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST, move |_, info| {
+            let size = match &info.data {
+                Some(gst::PadProbeData::Buffer(buffer)) => buffer.size(),
+                Some(gst::PadProbeData::BufferList(list)) => list.calculate_size(),
+                _ => 0,
+            };
+            collector.bytes.fetch_add(size as u64, Ordering::Relaxed);
+            gst::PadProbeReturn::Ok
+        });
+
+        let collector = Arc::clone(self);
+        decoded_pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            collector.frames.fetch_add(1, Ordering::Relaxed);
+            gst::PadProbeReturn::Ok
+        });
+        Ok(())
+    }
+
+    /// Remember the drop count an element reported in a QoS message.
+    pub(crate) fn record_qos(&self, qos: &gst::message::Qos) {
+        let (_, dropped) = qos.stats();
+        let gst::GenericFormattedValue::Buffers(Some(dropped)) = dropped else { return };
+        let source = qos.src().map(|src| src.path_string().to_string()).unwrap_or_default();
+        self.qos_dropped.lock().unwrap().insert(source, *dropped);
+    }
+
+    /// Compute a fresh snapshot. Rates cover the time since the previous call.
+    pub(crate) fn collect(&self, pipeline: &gst::Pipeline) -> StreamStats {
+        let frames = self.frames.load(Ordering::Relaxed);
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        let (elapsed, frame_delta, byte_delta) = {
+            let mut last = self.last_collect.lock().unwrap();
+            let now = Instant::now();
+            let deltas = (now - last.0, frames.saturating_sub(last.1), bytes.saturating_sub(last.2));
+            *last = (now, frames, bytes);
+            deltas
+        };
+
+        let (frames_rendered, frames_dropped) = sink_stats(pipeline);
+        StreamStats {
+            position: pipeline.query_position::<gst::ClockTime>().map(Duration::from),
+            duration: pipeline.query_duration::<gst::ClockTime>().map(Duration::from),
+            bitrate_bps: (per_second(byte_delta, elapsed) * 8.0) as u64,
+            fps: per_second(frame_delta, elapsed),
+            frames_decoded: frames,
+            frames_rendered,
+            frames_dropped,
+            qos_dropped: self.qos_dropped.lock().unwrap().values().sum(),
+            jitterbuffer: jitterbuffer_stats(pipeline),
+            rtp: rtp_stats(pipeline),
+            ..Default::default()
+        }
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    count as f64 / elapsed.as_secs_f64()
+}
+
+fn sink_stats(pipeline: &gst::Pipeline) -> (u64, u64) {
+    let Some(sink) = pipeline.by_name("videosink") else { return (0, 0) };
+    if sink.find_property("stats").is_none() {
+        return (0, 0);
+    }
+    let stats = sink.property::<gst::Structure>("stats");
+    (
+        stats.get::<u64>("rendered").unwrap_or(0),
+        stats.get::<u64>("dropped").unwrap_or(0),
+    )
+}
+
+fn jitterbuffer_stats(pipeline: &gst::Pipeline) -> JitterbufferStats {
+    let Some(jitterbuffer) = pipeline.by_name("jitterbuffer") else { return JitterbufferStats::default() };
+    let stats = jitterbuffer.property::<gst::Structure>("stats");
+    JitterbufferStats {
+        pushed: stats.get::<u64>("num-pushed").unwrap_or(0),
+        lost: stats.get::<u64>("num-lost").unwrap_or(0),
+        late: stats.get::<u64>("num-late").unwrap_or(0),
+        duplicates: stats.get::<u64>("num-duplicates").unwrap_or(0),
+        avg_jitter: Duration::from_nanos(stats.get::<u64>("avg-jitter").unwrap_or(0)),
+    }
+}
+
+/// Receiver statistics of the remote senders in every session of the
+/// `rtpbin` inside `rtspsrc`.
+fn rtp_stats(pipeline: &gst::Pipeline) -> RtpStats {
+    let mut total = RtpStats::default();
+    let manager = pipeline
+        .by_name("src")
+        .and_then(|src| src.downcast::<gst::Bin>().ok())
+        .and_then(|src| src.by_name("manager"));
+    let Some(manager) = manager else { return total };
+
+    // rtspsrc uses one session per media stream, numbered from zero
+    for session_id in 0u32..16 {
+        let Some(session) = manager.emit_by_name::<Option<glib::Object>>("get-session", &[&session_id]) else { break };
+        let stats = session.property::<gst::Structure>("stats");
+        let Ok(sources) = stats.get::<glib::ValueArray>("source-stats") else { continue };
+
+        for source in sources.iter().filter_map(|value| value.get::<gst::Structure>().ok()) {
+            let internal = source.get::<bool>("internal").unwrap_or(true);
+            let is_sender = source.get::<bool>("is-sender").unwrap_or(false);
+            if internal || !is_sender {
+                continue;
+            }
+            total.packets_received += source.get::<u64>("packets-received").unwrap_or(0);
+            total.packets_lost += source.get::<i32>("packets-lost").unwrap_or(0) as i64;
+            total.bitrate_bps += source.get::<u64>("bitrate").unwrap_or(0);
+
+            let clock_rate = source.get::<i32>("clock-rate").unwrap_or(0);
+            if clock_rate > 0 {
+                let jitter = source.get::<u32>("jitter").unwrap_or(0) as f64 / clock_rate as f64;
+                total.jitter = total.jitter.max(Duration::from_secs_f64(jitter));
+            }
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates() {
+        assert_eq!(per_second(50, Duration::from_secs(2)), 25.0);
+        assert_eq!(per_second(50, Duration::ZERO), 0.0);
+    }
+}
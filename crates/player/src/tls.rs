@@ -0,0 +1,286 @@
+use crate::PlayerError;
+use gio::prelude::*;
+use glib::subclass::prelude::*;
+use glib::translate::*;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Name of the element message the `accept-certificate` handler posts.
+pub(crate) const CERTIFICATE_MESSAGE: &str = "rtsp-player-certificate";
+
+/// Certificate problems that fail validation. All are enforced by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlsValidationFlags {
+    pub unknown_ca: bool,
+    pub bad_identity: bool,
+    pub not_activated: bool,
+    pub expired: bool,
+    pub revoked: bool,
+    pub insecure: bool,
+}
+
+impl TlsValidationFlags {
+    /// Check nothing. Prefer [`TlsOptions::accept_invalid_certificates`], which reports what it lets through.
+    pub fn none() -> Self {
+        TlsValidationFlags {
+            unknown_ca: false,
+            bad_identity: false,
+            not_activated: false,
+            expired: false,
+            revoked: false,
+            insecure: false,
+        }
+    }
+
+    pub(crate) fn to_gio(self) -> gio::TlsCertificateFlags {
+        let mut flags = gio::TlsCertificateFlags::empty();
+        for (enabled, flag) in [
+            (self.unknown_ca, gio::TlsCertificateFlags::UNKNOWN_CA),
+            (self.bad_identity, gio::TlsCertificateFlags::BAD_IDENTITY),
+            (self.not_activated, gio::TlsCertificateFlags::NOT_ACTIVATED),
+            (self.expired, gio::TlsCertificateFlags::EXPIRED),
+            (self.revoked, gio::TlsCertificateFlags::REVOKED),
+            (self.insecure, gio::TlsCertificateFlags::INSECURE),
+        ] {
+            if enabled {
+                flags |= flag;
+            }
+        }
+        flags | gio::TlsCertificateFlags::GENERIC_ERROR
+    }
+}
+
+impl Default for TlsValidationFlags {
+    fn default() -> Self {
+        TlsValidationFlags {
+            unknown_ca: true,
+            bad_identity: true,
+            not_activated: true,
+            expired: true,
+            revoked: true,
+            insecure: true,
+        }
+    }
+}
+
+/// Whether RTP itself is encrypted (SRTP) in addition to the RTSP connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SrtpMode {
+    /// Accept plain RTP or SRTP, whatever the server offers.
+    #[default]
+    Auto,
+    /// Only accept SRTP (`RTP/SAVP` and `RTP/SAVPF` profiles).
+    Required,
+    /// Only accept plain RTP.
+    Disabled,
+}
+
+impl SrtpMode {
+    /// Value for the `profiles` flags property of `rtspsrc`.
+    pub(crate) fn profiles(&self) -> &'static str {
+        match self {
+            SrtpMode::Auto => "avp+avpf+savp+savpf",
+            SrtpMode::Required => "savp+savpf",
+            SrtpMode::Disabled => "avp+avpf",
+        }
+    }
+}
+
+/// PEM files of a client certificate and its private key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    pub certificate: PathBuf,
+    pub private_key: PathBuf,
+}
+
+/// TLS settings for `rtsps://` streams.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TlsOptions {
+    /// PEM bundle of trusted CAs, replacing the system trust store.
+    pub ca_file: Option<PathBuf>,
+    pub client_certificate: Option<ClientCertificate>,
+    pub validation: TlsValidationFlags,
+    /// Accept any server certificate. Problems are still reported through
+    /// `PlayerEvent::CertificateError`. Meant for self-signed lab cameras.
+    pub accept_invalid_certificates: bool,
+    pub srtp: SrtpMode,
+}
+
+impl TlsOptions {
+    pub fn accept_invalid_certificates() -> Self {
+        TlsOptions { accept_invalid_certificates: true, ..Default::default() }
+    }
+
+    pub(crate) fn validate(&self) -> std::result::Result<(), PlayerError> {
+        let files = self.ca_file.iter().chain(
+            self.client_certificate
+                .iter()
+                .flat_map(|client| [&client.certificate, &client.private_key]),
+        );
+        for path in files {
+            if !path.is_file() {
+                return Err(PlayerError::ConfigError(format!("TLS file {} does not exist", path.display())));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set the TLS properties of `rtspsrc` and validate certificates in the
+    /// `accept-certificate` handler, posting the outcome on the bus.
+    pub(crate) fn apply(&self, src: &gst::Element) -> std::result::Result<(), PlayerError> {
+        if let Some(ca_file) = &self.ca_file {
+            let database = gio::TlsFileDatabase::new(ca_file)
+                .map_err(|err| PlayerError::ConfigError(format!("Cannot load CA file {}: {}", ca_file.display(), err)))?;
+            src.set_property("tls-database", &database);
+        }
+        if let Some(client) = &self.client_certificate {
+            let certificate = gio::TlsCertificate::from_files(&client.certificate, &client.private_key)
+                .map_err(|err| PlayerError::ConfigError(format!("Cannot load client certificate: {}", err)))?;
+            src.set_property("tls-interaction", ClientCertInteraction::new(certificate));
+        }
+        let enforced = self.validation.to_gio();
+        src.set_property("tls-validation-flags", enforced);
+        src.set_property_from_str("profiles", self.srtp.profiles());
+
+        // Only called when validation found problems
+        let accept_invalid = self.accept_invalid_certificates;
+        src.connect("accept-certificate", false, move |args| {
+            // A gboolean signal must always get a value back, so reject on bad arguments
+            let (Ok(src), Ok(errors)) = (args[0].get::<gst::Element>(), args[3].get::<gio::TlsCertificateFlags>()) else {
+                return Some(false.to_value());
+            };
+            let accepted = accept_invalid || (errors & enforced).is_empty();
+
+            let structure = gst::Structure::builder(CERTIFICATE_MESSAGE)
+                .field("errors", errors.bits())
+                .field("accepted", accepted)
+                .build();
+            let _ = src.post_message(gst::message::Element::builder(structure).src(&src).build());
+            Some(accepted.to_value())
+        });
+        Ok(())
+    }
+}
+
+/// A reason the server certificate failed validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateProblem {
+    UnknownCa,
+    BadIdentity,
+    NotActivated,
+    Expired,
+    Revoked,
+    Insecure,
+    Other,
+}
+
+impl CertificateProblem {
+    pub(crate) fn from_bits(bits: u32) -> Vec<CertificateProblem> {
+        let flags = gio::TlsCertificateFlags::from_bits_truncate(bits);
+        [
+            (gio::TlsCertificateFlags::UNKNOWN_CA, CertificateProblem::UnknownCa),
+            (gio::TlsCertificateFlags::BAD_IDENTITY, CertificateProblem::BadIdentity),
+            (gio::TlsCertificateFlags::NOT_ACTIVATED, CertificateProblem::NotActivated),
+            (gio::TlsCertificateFlags::EXPIRED, CertificateProblem::Expired),
+            (gio::TlsCertificateFlags::REVOKED, CertificateProblem::Revoked),
+            (gio::TlsCertificateFlags::INSECURE, CertificateProblem::Insecure),
+            (gio::TlsCertificateFlags::GENERIC_ERROR, CertificateProblem::Other),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .map(|(_, problem)| problem)
+        .collect()
+    }
+}
+
+mod imp {
+    use super::*;
+
+    /// Hands the configured client certificate to the TLS connection when
+    /// the server asks for one.
+    #[derive(Default)]
+    pub(crate) struct ClientCertInteraction {
+        pub(super) certificate: OnceLock<gio::TlsCertificate>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ClientCertInteraction {
+        const NAME: &'static str = "RtspPlayerClientCertInteraction";
+        type Type = super::ClientCertInteraction;
+        type ParentType = gio::TlsInteraction;
+    }
+
+    impl ObjectImpl for ClientCertInteraction {}
+}
+
+glib::wrapper! {
+    pub(crate) struct ClientCertInteraction(ObjectSubclass<imp::ClientCertInteraction>)
+        @extends gio::TlsInteraction;
+}
+
+impl ClientCertInteraction {
+    fn new(certificate: gio::TlsCertificate) -> Self {
+        let interaction: Self = glib::Object::new();
+        let _ = interaction.imp().certificate.set(certificate);
+        interaction
+    }
+}
+
+// gio has no subclassing support for GTlsInteraction, so the
+// request_certificate virtual method is installed by hand.
+//
+// SAFETY: `#[glib::object_subclass]` registers the type with GTlsInteraction
+// as parent, so its class and instance structs start with GTlsInteractionClass
+// and GTlsInteraction. `class.as_mut()` therefore points at the parent class
+// struct, and `request_certificate` is only ever called with instances of
+// this type, so casting them to its instance struct is sound.
+unsafe impl IsSubclassable<imp::ClientCertInteraction> for gio::TlsInteraction {
+    fn class_init(class: &mut glib::Class<Self>) {
+        Self::parent_class_init::<imp::ClientCertInteraction>(class);
+        let klass = class.as_mut();
+        klass.request_certificate = Some(request_certificate);
+    }
+}
+
+unsafe extern "C" fn request_certificate(
+    interaction: *mut gio::ffi::GTlsInteraction,
+    connection: *mut gio::ffi::GTlsConnection,
+    _flags: gio::ffi::GTlsCertificateRequestFlags,
+    _cancellable: *mut gio::ffi::GCancellable,
+    _error: *mut *mut glib::ffi::GError,
+) -> gio::ffi::GTlsInteractionResult {
+    unsafe {
+        let instance = &*(interaction as *mut <imp::ClientCertInteraction as ObjectSubclass>::Instance);
+        let Some(certificate) = instance.imp().certificate.get() else {
+            return gio::ffi::G_TLS_INTERACTION_UNHANDLED;
+        };
+        let connection: Borrowed<gio::TlsConnection> = from_glib_borrow(connection);
+        connection.set_certificate(certificate);
+        gio::ffi::G_TLS_INTERACTION_HANDLED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problems_from_flags() {
+        let bits = (gio::TlsCertificateFlags::UNKNOWN_CA | gio::TlsCertificateFlags::EXPIRED).bits();
+        assert_eq!(
+            CertificateProblem::from_bits(bits),
+            vec![CertificateProblem::UnknownCa, CertificateProblem::Expired]
+        );
+        assert!(CertificateProblem::from_bits(0).is_empty());
+    }
+
+    #[test]
+    fn test_validation_flags() {
+        assert!(TlsValidationFlags::default().to_gio().contains(gio::TlsCertificateFlags::UNKNOWN_CA));
+        let relaxed = TlsValidationFlags { unknown_ca: false, ..Default::default() }.to_gio();
+        assert!(!relaxed.contains(gio::TlsCertificateFlags::UNKNOWN_CA));
+        assert!(relaxed.contains(gio::TlsCertificateFlags::EXPIRED));
+    }
+}
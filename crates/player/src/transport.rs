@@ -0,0 +1,138 @@
+use crate::PlayerError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How RTP reaches the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Transport {
+    /// Let `rtspsrc` try UDP, then TCP, then HTTP tunneling.
+    #[default]
+    Auto,
+    UdpUnicast,
+    UdpMulticast,
+    /// RTP interleaved in the RTSP TCP connection.
+    TcpInterleaved,
+    /// RTSP and RTP tunneled through HTTP, for proxies that only pass HTTP.
+    HttpTunnel,
+}
+
+impl Transport {
+    /// Value for the `protocols` flags property of `rtspsrc`.
+    pub fn to_gst_flags(&self) -> &'static str {
+        match self {
+            Transport::Auto => "udp+tcp+http",
+            Transport::UdpUnicast => "udp",
+            Transport::UdpMulticast => "udp-mcast",
+            Transport::TcpInterleaved => "tcp",
+            Transport::HttpTunnel => "tcp+http",
+        }
+    }
+
+    /// Whether RTP arrives on UDP ports of our own, which `port-range` and
+    /// the TCP fallback apply to.
+    pub fn is_udp(&self) -> bool {
+        matches!(self, Transport::Auto | Transport::UdpUnicast | Transport::UdpMulticast)
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Transport::Auto => "auto",
+            Transport::UdpUnicast => "UDP",
+            Transport::UdpMulticast => "UDP multicast",
+            Transport::TcpInterleaved => "TCP",
+            Transport::HttpTunnel => "HTTP tunnel",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Notices when a UDP session delivers no data at all, which usually means a
+/// firewall drops the RTP packets while the RTSP connection itself works.
+///
+/// Armed on every (re)start, it watches the jitterbuffer input and disarms
+/// itself on the first buffer.
+#[derive(Debug, Default)]
+pub(crate) struct FallbackWatch {
+    started: Mutex<Option<Instant>>,
+    received: AtomicBool,
+}
+
+impl FallbackWatch {
+    pub(crate) fn arm(&self) {
+        self.received.store(false, Ordering::Relaxed);
+        *self.started.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub(crate) fn disarm(&self) {
+        *self.started.lock().unwrap() = None;
+    }
+
+    /// Time without any data if it exceeds `timeout`. Reports once per arm.
+    pub(crate) fn check(&self, timeout: Duration) -> Option<Duration> {
+        self.check_at(Instant::now(), timeout)
+    }
+
+    fn check_at(&self, now: Instant, timeout: Duration) -> Option<Duration> {
+        let mut started = self.started.lock().unwrap();
+        if self.received.load(Ordering::Relaxed) {
+            *started = None;
+            return None;
+        }
+        let waited = now.saturating_duration_since((*started)?);
+        if waited < timeout {
+            return None;
+        }
+        *started = None;
+        Some(waited)
+    }
+
+    /// Watch RTP entering the jitterbuffer of `pipeline`.
+    pub(crate) fn attach(self: &Arc<Self>, pipeline: &gst::Pipeline) -> std::result::Result<(), PlayerError> {
+        let pad = pipeline
+            .by_name("jitterbuffer")
+            .and_then(|jitterbuffer| jitterbuffer.static_pad("sink"))
+            .ok_or_else(|| PlayerError::InitError("Could not find the jitterbuffer input".into()))?;
+
+        let watch = Arc::clone(self);
+        pad.add_probe(gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST, move |_, _| {
+            watch.received.store(true, Ordering::Relaxed);
+            gst::PadProbeReturn::Ok
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_only_without_data() {
+        let watch = FallbackWatch::default();
+        let timeout = Duration::from_secs(5);
+        assert_eq!(watch.check(timeout), None, "disarmed watch never fires");
+
+        watch.arm();
+        let armed_at = watch.started.lock().unwrap().unwrap();
+        assert_eq!(watch.check_at(armed_at + Duration::from_secs(1), timeout), None);
+        assert_eq!(watch.check_at(armed_at + Duration::from_secs(6), timeout), Some(Duration::from_secs(6)));
+        assert_eq!(watch.check_at(armed_at + Duration::from_secs(7), timeout), None);
+
+        watch.arm();
+        watch.received.store(true, Ordering::Relaxed);
+        assert_eq!(watch.check_at(Instant::now() + Duration::from_secs(60), timeout), None);
+    }
+
+    #[test]
+    fn test_gst_flags() {
+        assert_eq!(Transport::default().to_gst_flags(), "udp+tcp+http");
+        assert_eq!(Transport::HttpTunnel.to_gst_flags(), "tcp+http");
+        assert!(!Transport::TcpInterleaved.is_udp());
+    }
+}
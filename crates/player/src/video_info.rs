@@ -0,0 +1,102 @@
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+
+/// Properties of the video stream, read from the negotiated caps.
+///
+/// Geometry, frame rate and color come from the decoded caps, codec details
+/// from the encoded caps in front of the decoder. Fields stay at their
+/// defaults until the corresponding caps have been negotiated.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VideoInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second, 0.0 for variable frame rate streams.
+    pub framerate: f64,
+    /// Pixel aspect ratio as numerator and denominator.
+    pub pixel_aspect_ratio: (i32, i32),
+    /// E.g. `bt709`, `None` when unknown.
+    pub colorimetry: Option<String>,
+    /// E.g. `progressive` or `interleaved`.
+    pub interlace_mode: Option<String>,
+    /// E.g. `H.264`, or the RTP encoding name for codecs without a parser.
+    pub codec: String,
+    pub profile: Option<String>,
+    pub level: Option<String>,
+}
+
+impl VideoInfo {
+    /// Take geometry, frame rate and color from decoded (`video/x-raw`) caps.
+    pub(crate) fn update_from_decoded(&mut self, caps: &gst::CapsRef) -> bool {
+        let Ok(info) = gst_video::VideoInfo::from_caps(caps) else { return false };
+        let fps = info.fps();
+        let par = info.par();
+
+        self.width = info.width();
+        self.height = info.height();
+        self.framerate = if fps.denom() > 0 { fps.numer() as f64 / fps.denom() as f64 } else { 0.0 };
+        self.pixel_aspect_ratio = (par.numer(), par.denom());
+        self.colorimetry = Some(info.colorimetry().to_string()).filter(|c| !c.is_empty());
+        self.interlace_mode = Some(info.interlace_mode().to_str().to_string());
+        true
+    }
+
+    /// Take codec, profile and level from the encoded caps entering the decoder.
+    pub(crate) fn update_from_encoded(&mut self, caps: &gst::CapsRef) -> bool {
+        let Some(structure) = caps.structure(0) else { return false };
+        let Some(codec) = codec_name(structure) else { return false };
+
+        self.codec = codec;
+        self.profile = structure.get::<String>("profile").ok();
+        self.level = structure.get::<String>("level").ok();
+        true
+    }
+}
+
+fn codec_name(structure: &gst::StructureRef) -> Option<String> {
+    let name = match structure.name().as_str() {
+        "video/x-h264" => "H.264",
+        "video/x-h265" => "H.265",
+        "video/x-vp8" => "VP8",
+        "video/x-vp9" => "VP9",
+        "video/x-av1" => "AV1",
+        "image/jpeg" => "JPEG",
+        "video/mpeg" => "MPEG-4 Part 2",
+        "application/x-rtp" => return structure.get::<String>("encoding-name").ok(),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_from_caps() {
+        gst::init().unwrap();
+        let mut info = VideoInfo::default();
+
+        let decoded = gst::Caps::builder("video/x-raw")
+            .field("format", "I420")
+            .field("width", 1920i32)
+            .field("height", 1080i32)
+            .field("framerate", gst::Fraction::new(30000, 1001))
+            .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+            .field("interlace-mode", "progressive")
+            .build();
+        assert!(info.update_from_decoded(&decoded));
+        assert_eq!((info.width, info.height), (1920, 1080));
+        assert!((info.framerate - 29.97).abs() < 0.01);
+        assert_eq!(info.interlace_mode.as_deref(), Some("progressive"));
+
+        let encoded = gst::Caps::builder("video/x-h264")
+            .field("profile", "high")
+            .field("level", "4.1")
+            .build();
+        assert!(info.update_from_encoded(&encoded));
+        assert_eq!(info.codec, "H.264");
+        assert_eq!(info.profile.as_deref(), Some("high"));
+        assert_eq!(info.level.as_deref(), Some("4.1"));
+        assert_eq!(info.width, 1920, "encoded caps keep the decoded geometry");
+    }
+}
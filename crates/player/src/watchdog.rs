@@ -0,0 +1,94 @@
+use crate::PlayerError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How often the watchdog timer looks at the buffer flow.
+pub(crate) const WATCHDOG_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Detects streams that stay connected but stop delivering frames.
+///
+/// A pad probe after the decoder records when the last buffer went through.
+/// The watchdog is only armed while the stream is supposed to be running, so
+/// pauses and reconnect backoffs never count as stalls.
+#[derive(Debug, Default)]
+pub(crate) struct Watchdog {
+    last_activity: Mutex<Option<Instant>>,
+}
+
+impl Watchdog {
+    /// Start (or restart) the no-data countdown.
+    pub(crate) fn arm(&self) {
+        *self.last_activity.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub(crate) fn disarm(&self) {
+        *self.last_activity.lock().unwrap() = None;
+    }
+
+    fn feed(&self) {
+        let mut last_activity = self.last_activity.lock().unwrap();
+        if last_activity.is_some() {
+            *last_activity = Some(Instant::now());
+        }
+    }
+
+    /// Time without data if it exceeds `timeout`. Disarms the watchdog so a
+    /// stall is reported only once per arm.
+    pub(crate) fn check(&self, timeout: Duration) -> Option<Duration> {
+        self.check_at(Instant::now(), timeout)
+    }
+
+    fn check_at(&self, now: Instant, timeout: Duration) -> Option<Duration> {
+        let mut last_activity = self.last_activity.lock().unwrap();
+        let idle = now.saturating_duration_since((*last_activity)?);
+        if idle < timeout {
+            return None;
+        }
+        *last_activity = None;
+        Some(idle)
+    }
+
+    /// Count buffers leaving the decoder of `pipeline`.
+    pub(crate) fn attach(self: &Arc<Self>, pipeline: &gst::Pipeline) -> std::result::Result<(), PlayerError> {
+        let pad = pipeline
+            .by_name("convert")
+            .and_then(|convert| convert.static_pad("sink"))
+            .ok_or_else(|| PlayerError::InitError("Could not find the decoded video pad".into()))?;
+
+        let watchdog = Arc::clone(self);
+        pad.add_probe(gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST, move |_, _| {
+            watchdog.feed();
+            gst::PadProbeReturn::Ok
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_reported_once_per_arm() {
+        let watchdog = Watchdog::default();
+        let timeout = Duration::from_secs(5);
+        assert_eq!(watchdog.check(timeout), None, "disarmed watchdog never fires");
+
+        watchdog.arm();
+        let armed_at = watchdog.last_activity.lock().unwrap().unwrap();
+        assert_eq!(watchdog.check_at(armed_at + Duration::from_secs(1), timeout), None);
+
+        let stalled = watchdog.check_at(armed_at + Duration::from_secs(6), timeout);
+        assert_eq!(stalled, Some(Duration::from_secs(6)));
+        assert_eq!(watchdog.check_at(armed_at + Duration::from_secs(7), timeout), None);
+    }
+
+    #[test]
+    fn test_feed_only_when_armed() {
+        let watchdog = Watchdog::default();
+        watchdog.feed();
+        assert!(watchdog.last_activity.lock().unwrap().is_none());
+    }
+}
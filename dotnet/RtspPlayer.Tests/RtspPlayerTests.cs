@@ -0,0 +1,89 @@
+using System;
+using System.Threading;
+using RtspPlayerNet;
+using Xunit;
+
+namespace RtspPlayerNet.Tests
+{
+    // These load the native library built by `cargo build -p player-sys`, so they
+    // run on Linux as well. None of them needs a camera: players are created
+    // headless and never reach a server.
+    public class RtspPlayerTests
+    {
+        private const string Url = "rtsp://127.0.0.1:1/binding-test";
+
+        [Fact]
+        public void InvalidUrlReportsInvalidConfig()
+        {
+            var error = Assert.Throws<RtspPlayerException>(() => new RtspPlayer("http://camera/live"));
+            Assert.Equal(ErrorCode.InvalidConfig, error.Code);
+            Assert.Contains("rtsp://", error.Message);
+        }
+
+        [Fact]
+        public void UnknownTransportReportsInvalidArgument()
+        {
+            var options = new RtspPlayerOptions { Headless = true, Transport = (Transport)42 };
+            var error = Assert.Throws<RtspPlayerException>(() => new RtspPlayer(Url, options));
+            Assert.Equal(ErrorCode.InvalidArgument, error.Code);
+        }
+
+        [Fact]
+        public void CreateAndDisposeWithEventHandler()
+        {
+            var player = new RtspPlayer(Url, new RtspPlayerOptions { Headless = true, Name = "binding-test" });
+            int events = 0;
+            player.EventReceived += (_, _) => Interlocked.Increment(ref events);
+
+            Assert.False(player.IsPlaying);
+            Assert.False(player.Handle.IsInvalid);
+
+            player.Dispose();
+            Assert.True(player.Handle.IsClosed);
+            Assert.Throws<ObjectDisposedException>(() => player.Play());
+            player.Dispose();
+        }
+
+        [Fact]
+        public void FrameHandlerRequiresFramesOption()
+        {
+            using var player = new RtspPlayer(Url, new RtspPlayerOptions { Headless = true });
+            var error = Assert.Throws<RtspPlayerException>(() => player.SetFrameHandler((in RtspFrame _) => { }));
+            Assert.Equal(ErrorCode.InvalidConfig, error.Code);
+        }
+
+        [Fact]
+        public void FrameHandlerCanBeReplacedAndRemoved()
+        {
+            using var player = new RtspPlayer(Url, new RtspPlayerOptions { Frames = true, FrameFormat = PixelFormat.Bgra });
+            // No server, so no frame arrives; this only exercises registration
+            player.SetFrameHandler((in RtspFrame _) => { });
+            player.SetFrameHandler((in RtspFrame _) => { });
+            player.SetFrameHandler(null);
+        }
+
+        [Fact]
+        public void StatsOfFreshPlayer()
+        {
+            using var player = new RtspPlayer(Url, new RtspPlayerOptions { Headless = true });
+            var stats = player.GetStats();
+            Assert.Equal(0UL, stats.FramesDecoded);
+            Assert.Equal(0u, stats.ReconnectAttempts);
+        }
+
+        [Fact]
+        public void SnapshotWithoutFrameReportsSnapshotError()
+        {
+            using var player = new RtspPlayer(Url, new RtspPlayerOptions { Headless = true });
+            var error = Assert.Throws<RtspPlayerException>(() => player.TakeSnapshot(PixelFormat.Bgra));
+            Assert.Equal(ErrorCode.Snapshot, error.Code);
+        }
+
+        [Fact]
+        public void StopRecordingWithoutRecordingIsIgnored()
+        {
+            using var player = new RtspPlayer(Url, new RtspPlayerOptions { Headless = true });
+            player.StopRecording();
+        }
+    }
+}
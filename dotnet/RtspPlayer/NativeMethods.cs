@@ -0,0 +1,156 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace RtspPlayerNet
+{
+    /// <summary>
+    /// P/Invoke declarations for <c>crates/player-sys/include/rtsp_player.h</c>.
+    /// </summary>
+    internal static class NativeMethods
+    {
+        // rtsp_player.dll on Windows, librtsp_player.so on Linux, librtsp_player.dylib on macOS
+        private const string Library = "rtsp_player";
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        internal delegate void EventCallback(IntPtr evt, IntPtr userData);
+
+        [UnmanagedFunctionPointer(CallingConvention.Cdecl)]
+        internal delegate void FrameCallback(IntPtr frame, IntPtr userData);
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct Options
+        {
+            public uint LatencyMs;
+            public uint Transport;
+            [MarshalAs(UnmanagedType.I1)] public bool Headless;
+            [MarshalAs(UnmanagedType.I1)] public bool Audio;
+            public IntPtr Username;
+            public IntPtr Password;
+            public IntPtr Name;
+            [MarshalAs(UnmanagedType.I1)] public bool Frames;
+            public uint FrameFormat;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct Snapshot
+        {
+            public IntPtr Data;
+            public UIntPtr Len;
+            public uint Width;
+            public uint Height;
+            public uint Stride;
+            public uint Format;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        internal struct Event
+        {
+            public uint Kind;
+            public uint State;
+            public uint OldState;
+            public int BufferingPercent;
+            public int ErrorCode;
+            public uint Attempt;
+            public ulong DurationMs;
+            public uint Width;
+            public uint Height;
+            public double Framerate;
+            public IntPtr Message;
+        }
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void rtsp_player_options_init(out Options options);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern RtspPlayerSafeHandle rtsp_player_create_with_options(
+            [MarshalAs(UnmanagedType.LPUTF8Str)] string url, ref Options options);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void rtsp_player_destroy(IntPtr handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_play(RtspPlayerSafeHandle handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_pause(RtspPlayerSafeHandle handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_stop(RtspPlayerSafeHandle handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_seek(RtspPlayerSafeHandle handle, double position);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_is_playing(RtspPlayerSafeHandle handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_set_hwnd(RtspPlayerSafeHandle handle, IntPtr hwnd);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_snapshot(RtspPlayerSafeHandle handle, uint format, out Snapshot snapshot);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void rtsp_player_snapshot_free(ref Snapshot snapshot);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_snapshot_to_file(
+            RtspPlayerSafeHandle handle, [MarshalAs(UnmanagedType.LPUTF8Str)] string path);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_start_recording(
+            RtspPlayerSafeHandle handle, [MarshalAs(UnmanagedType.LPUTF8Str)] string path);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_stop_recording(RtspPlayerSafeHandle handle);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_get_stats(RtspPlayerSafeHandle handle, out RtspStats stats);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_set_event_callback(
+            RtspPlayerSafeHandle handle, EventCallback? callback, IntPtr userData);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        [return: MarshalAs(UnmanagedType.I1)]
+        internal static extern bool rtsp_player_set_frame_callback(
+            RtspPlayerSafeHandle handle, FrameCallback? callback, IntPtr userData);
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern int rtsp_player_get_last_error_code();
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern IntPtr rtsp_player_get_last_error();
+
+        [DllImport(Library, CallingConvention = CallingConvention.Cdecl)]
+        internal static extern void rtsp_player_free_string(IntPtr text);
+
+        /// <summary>
+        /// Turns the last error of this thread into an exception. Call it right
+        /// after the failed native call, on the same thread.
+        /// </summary>
+        internal static RtspPlayerException LastError(string action)
+        {
+            var code = (ErrorCode)rtsp_player_get_last_error_code();
+            var text = rtsp_player_get_last_error();
+            string message = "unknown error";
+            if (text != IntPtr.Zero)
+            {
+                message = Marshal.PtrToStringUTF8(text) ?? message;
+                rtsp_player_free_string(text);
+            }
+            return new RtspPlayerException(code, $"{action}: {message}");
+        }
+    }
+}
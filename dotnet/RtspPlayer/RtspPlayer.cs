@@ -0,0 +1,245 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace RtspPlayerNet
+{
+    /// <summary>
+    /// An RTSP player backed by the native <c>rtsp_player</c> library.
+    /// Failed calls throw <see cref="RtspPlayerException"/>.
+    /// </summary>
+    public sealed class RtspPlayer : IDisposable
+    {
+        private readonly RtspPlayerSafeHandle _handle;
+        private readonly object _eventLock = new object();
+        private EventHandler<RtspEventArgs>? _eventReceived;
+
+        /// <summary>
+        /// Creates a stopped player for <paramref name="url"/>.
+        /// </summary>
+        public RtspPlayer(string url) : this(url, new RtspPlayerOptions())
+        {
+        }
+
+        /// <summary>
+        /// Creates a stopped player for <paramref name="url"/> with <paramref name="options"/>.
+        /// </summary>
+        public RtspPlayer(string url, RtspPlayerOptions options)
+        {
+            if (string.IsNullOrEmpty(url))
+                throw new ArgumentException("RTSP URL cannot be null or empty", nameof(url));
+            if (options == null)
+                throw new ArgumentNullException(nameof(options));
+
+            NativeMethods.rtsp_player_options_init(out var native);
+            if (options.Latency is TimeSpan latency)
+                native.LatencyMs = checked((uint)latency.TotalMilliseconds);
+            if (options.Audio is bool audio)
+                native.Audio = audio;
+            native.Transport = (uint)options.Transport;
+            native.Headless = options.Headless;
+            native.Frames = options.Frames;
+            native.FrameFormat = (uint)options.FrameFormat;
+
+            try
+            {
+                native.Username = Marshal.StringToCoTaskMemUTF8(options.Username);
+                native.Password = Marshal.StringToCoTaskMemUTF8(options.Password);
+                native.Name = Marshal.StringToCoTaskMemUTF8(options.Name);
+
+                _handle = NativeMethods.rtsp_player_create_with_options(url, ref native);
+                if (_handle.IsInvalid)
+                    throw NativeMethods.LastError("Failed to create RTSP player");
+            }
+            finally
+            {
+                Marshal.FreeCoTaskMem(native.Username);
+                Marshal.FreeCoTaskMem(native.Password);
+                Marshal.FreeCoTaskMem(native.Name);
+            }
+        }
+
+        /// <summary>
+        /// The native handle, for calls this binding does not wrap.
+        /// </summary>
+        public RtspPlayerSafeHandle Handle => _handle;
+
+        /// <summary>
+        /// Raised for every player event, on a thread owned by the native player,
+        /// one event at a time. Exceptions thrown by handlers are swallowed since
+        /// they cannot cross into native code.
+        /// </summary>
+        public event EventHandler<RtspEventArgs>? EventReceived
+        {
+            add
+            {
+                lock (_eventLock)
+                {
+                    bool first = _eventReceived == null;
+                    _eventReceived += value;
+                    if (first && _eventReceived != null)
+                        SetCallback(OnNativeEvent);
+                }
+            }
+            remove
+            {
+                lock (_eventLock)
+                {
+                    _eventReceived -= value;
+                    if (_eventReceived == null && !_handle.IsClosed)
+                        SetCallback(null);
+                }
+            }
+        }
+
+        public bool IsPlaying
+        {
+            get { return NativeMethods.rtsp_player_is_playing(_handle); }
+        }
+
+        public void Play() => Check(NativeMethods.rtsp_player_play(_handle), "Failed to play");
+
+        public void Pause() => Check(NativeMethods.rtsp_player_pause(_handle), "Failed to pause");
+
+        public void Stop() => Check(NativeMethods.rtsp_player_stop(_handle), "Failed to stop");
+
+        /// <summary>
+        /// Seeks to <paramref name="position"/>, a fraction of the duration
+        /// between 0.0 and 1.0. Live streams ignore it.
+        /// </summary>
+        public void Seek(double position) => Check(NativeMethods.rtsp_player_seek(_handle, position), "Failed to seek");
+
+        /// <summary>
+        /// Renders into a native window: an HWND on Windows, an X11 window id elsewhere.
+        /// The window must outlive the player.
+        /// </summary>
+        public void AttachToWindow(IntPtr window) =>
+            Check(NativeMethods.rtsp_player_set_hwnd(_handle, window), "Failed to attach to window");
+
+        /// <summary>
+        /// Copies the last rendered frame in <paramref name="format"/>.
+        /// </summary>
+        public Snapshot TakeSnapshot(PixelFormat format = PixelFormat.Rgba)
+        {
+            Check(NativeMethods.rtsp_player_snapshot(_handle, (uint)format, out var native), "Failed to take snapshot");
+            try
+            {
+                var data = new byte[checked((int)native.Len)];
+                Marshal.Copy(native.Data, data, 0, data.Length);
+                return new Snapshot(data, (int)native.Width, (int)native.Height, (int)native.Stride, (PixelFormat)native.Format);
+            }
+            finally
+            {
+                NativeMethods.rtsp_player_snapshot_free(ref native);
+            }
+        }
+
+        /// <summary>
+        /// Saves the last rendered frame as JPEG (<c>.jpg</c>, <c>.jpeg</c>) or PNG.
+        /// </summary>
+        public void SaveSnapshot(string path) =>
+            Check(NativeMethods.rtsp_player_snapshot_to_file(_handle, path), "Failed to save snapshot");
+
+        /// <summary>
+        /// Records to <paramref name="path"/> without re-encoding; the container follows the extension.
+        /// </summary>
+        public void StartRecording(string path) =>
+            Check(NativeMethods.rtsp_player_start_recording(_handle, path), "Failed to start recording");
+
+        public void StopRecording() => Check(NativeMethods.rtsp_player_stop_recording(_handle), "Failed to stop recording");
+
+        /// <summary>
+        /// The latest statistics, refreshed once per second by default.
+        /// </summary>
+        public RtspStats GetStats()
+        {
+            Check(NativeMethods.rtsp_player_get_stats(_handle, out var stats), "Failed to get stats");
+            return stats;
+        }
+
+        /// <summary>
+        /// Calls <paramref name="handler"/> with every decoded frame, replacing the
+        /// previous one; null removes it. The player must be created with
+        /// <see cref="RtspPlayerOptions.Frames"/>. Once this returns the previous
+        /// handler is not running and will not be called again.
+        /// </summary>
+        public void SetFrameHandler(FrameHandler? handler)
+        {
+            NativeMethods.FrameCallback? callback = null;
+            if (handler != null)
+            {
+                callback = (frame, _) =>
+                {
+                    try
+                    {
+                        handler(ReadFrame(frame));
+                    }
+                    catch (Exception e)
+                    {
+                        Console.Error.WriteLine($"RtspPlayer frame handler failed: {e}");
+                    }
+                };
+            }
+
+            ThrowIfDisposed();
+            var previous = _handle.FrameCallback;
+            _handle.FrameCallback = callback;
+            if (!NativeMethods.rtsp_player_set_frame_callback(_handle, callback, IntPtr.Zero))
+            {
+                _handle.FrameCallback = previous;
+                throw NativeMethods.LastError("Failed to set frame handler");
+            }
+            GC.KeepAlive(previous);
+        }
+
+        /// <summary>
+        /// Stops the player and releases the native handle. Once it returns no
+        /// more events or frames are delivered.
+        /// </summary>
+        public void Dispose()
+        {
+            _handle.Dispose();
+        }
+
+        private void SetCallback(NativeMethods.EventCallback? callback)
+        {
+            ThrowIfDisposed();
+            // Replace the delegate only after the native side stopped using the old one
+            var previous = _handle.Callback;
+            _handle.Callback = callback;
+            if (!NativeMethods.rtsp_player_set_event_callback(_handle, callback, IntPtr.Zero))
+            {
+                _handle.Callback = previous;
+                throw NativeMethods.LastError("Failed to set event callback");
+            }
+            GC.KeepAlive(previous);
+        }
+
+        private void OnNativeEvent(IntPtr evt, IntPtr userData)
+        {
+            try
+            {
+                var native = Marshal.PtrToStructure<NativeMethods.Event>(evt);
+                _eventReceived?.Invoke(this, new RtspEventArgs(native));
+            }
+            catch (Exception e)
+            {
+                Console.Error.WriteLine($"RtspPlayer event handler failed: {e}");
+            }
+        }
+
+        private static unsafe ref readonly RtspFrame ReadFrame(IntPtr frame) => ref *(RtspFrame*)frame;
+
+        private void Check(bool ok, string action)
+        {
+            // A disposed handle already throws ObjectDisposedException while marshaling
+            if (!ok)
+                throw NativeMethods.LastError(action);
+        }
+
+        private void ThrowIfDisposed()
+        {
+            if (_handle.IsClosed)
+                throw new ObjectDisposedException(nameof(RtspPlayer));
+        }
+    }
+}
@@ -0,0 +1,17 @@
+using System;
+
+namespace RtspPlayerNet
+{
+    /// <summary>
+    /// A failed native call, with the code reported by the player.
+    /// </summary>
+    public class RtspPlayerException : Exception
+    {
+        public ErrorCode Code { get; }
+
+        public RtspPlayerException(ErrorCode code, string message) : base(message)
+        {
+            Code = code;
+        }
+    }
+}
@@ -0,0 +1,29 @@
+using System;
+using Microsoft.Win32.SafeHandles;
+
+namespace RtspPlayerNet
+{
+    /// <summary>
+    /// Owns a native player and destroys it exactly once, even if the
+    /// <see cref="RtspPlayer"/> is never disposed.
+    /// </summary>
+    public sealed class RtspPlayerSafeHandle : SafeHandleZeroOrMinusOneIsInvalid
+    {
+        // Kept here rather than in RtspPlayer so the delegates stay alive until
+        // rtsp_player_destroy has returned, which guarantees no further calls.
+        internal NativeMethods.EventCallback? Callback;
+        internal NativeMethods.FrameCallback? FrameCallback;
+
+        public RtspPlayerSafeHandle() : base(true)
+        {
+        }
+
+        protected override bool ReleaseHandle()
+        {
+            NativeMethods.rtsp_player_destroy(handle);
+            Callback = null;
+            FrameCallback = null;
+            return true;
+        }
+    }
+}
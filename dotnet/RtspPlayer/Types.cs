@@ -0,0 +1,229 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace RtspPlayerNet
+{
+    /// <summary>
+    /// Mirrors the <c>RTSP_ERROR_*</c> constants.
+    /// </summary>
+    public enum ErrorCode
+    {
+        Ok = 0,
+        Init = 1,
+        InvalidConfig = 2,
+        Authentication = 3,
+        Connection = 4,
+        Timeout = 5,
+        UnsupportedCodec = 6,
+        MissingPlugin = 7,
+        StateChange = 8,
+        Sink = 9,
+        Stream = 10,
+        Windows = 11,
+        Recording = 12,
+        Snapshot = 13,
+        LimitReached = 14,
+        InvalidArgument = 15,
+        Panic = 16,
+    }
+
+    /// <summary>
+    /// Mirrors the <c>RTSP_TRANSPORT_*</c> constants.
+    /// </summary>
+    public enum Transport : uint
+    {
+        Auto = 0,
+        Udp = 1,
+        UdpMulticast = 2,
+        Tcp = 3,
+        Http = 4,
+    }
+
+    /// <summary>
+    /// Mirrors the <c>RTSP_PIXEL_FORMAT_*</c> constants.
+    /// </summary>
+    public enum PixelFormat : uint
+    {
+        Rgb = 0,
+        Rgba = 1,
+        Bgr = 2,
+        Bgra = 3,
+        Gray8 = 4,
+    }
+
+    /// <summary>
+    /// Mirrors the <c>RTSP_STATE_*</c> constants.
+    /// </summary>
+    public enum PlayerState : uint
+    {
+        Stopped = 0,
+        Ready = 1,
+        Paused = 2,
+        Playing = 3,
+    }
+
+    /// <summary>
+    /// Mirrors the <c>RTSP_EVENT_*</c> constants.
+    /// </summary>
+    public enum EventKind : uint
+    {
+        StateChanged = 0,
+        StreamStarted = 1,
+        Buffering = 2,
+        EndOfStream = 3,
+        Error = 4,
+        AuthenticationFailed = 5,
+        CertificateError = 6,
+        Reconnecting = 7,
+        Reconnected = 8,
+        TransportFallback = 9,
+        Stalled = 10,
+        ReconnectGaveUp = 11,
+        RecordingStarted = 12,
+        RecordingStopped = 13,
+        SegmentClosed = 14,
+        VideoInfo = 15,
+    }
+
+    /// <summary>
+    /// Settings for a new player. The defaults match <c>rtsp_player_options_init</c>.
+    /// </summary>
+    public class RtspPlayerOptions
+    {
+        public TimeSpan? Latency { get; set; }
+        public Transport Transport { get; set; } = Transport.Auto;
+        /// <summary>Decode without opening a window.</summary>
+        public bool Headless { get; set; }
+        public bool? Audio { get; set; }
+        public string? Username { get; set; }
+        public string? Password { get; set; }
+        /// <summary>Name used in logs instead of the URL.</summary>
+        public string? Name { get; set; }
+        /// <summary>
+        /// Decode into memory for <see cref="RtspPlayer.SetFrameHandler"/> instead
+        /// of a window. Implies <see cref="Headless"/> and no audio.
+        /// </summary>
+        public bool Frames { get; set; }
+        public PixelFormat FrameFormat { get; set; } = PixelFormat.Rgba;
+    }
+
+    /// <summary>
+    /// Playback statistics, laid out like the native <c>RtspStats</c>.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RtspStats
+    {
+        /// <summary>-1 when unknown.</summary>
+        public long PositionMs;
+        /// <summary>-1 for live streams.</summary>
+        public long DurationMs;
+        /// <summary>Attempts since the stream last reached playing.</summary>
+        public uint ReconnectAttempts;
+        /// <summary>Attempts since the player was created.</summary>
+        public ulong ReconnectsTotal;
+        public ulong Stalls;
+        public ulong UptimeMs;
+        public ulong BitrateBps;
+        public double Fps;
+        public ulong FramesDecoded;
+        public ulong FramesRendered;
+        public ulong FramesDropped;
+        public ulong QosDropped;
+        public ulong JitterbufferLost;
+        public ulong JitterbufferLate;
+        public ulong PacketsReceived;
+        /// <summary>Negative when duplicates outnumber losses.</summary>
+        public long PacketsLost;
+        public double JitterMs;
+    }
+
+    /// <summary>
+    /// A decoded frame lent to a <see cref="FrameHandler"/> without copying,
+    /// laid out like the native <c>RtspFrame</c>. The pixels are only valid
+    /// until the handler returns.
+    /// </summary>
+    [StructLayout(LayoutKind.Sequential)]
+    public readonly struct RtspFrame
+    {
+        public readonly IntPtr Data;
+        public readonly UIntPtr Length;
+        public readonly uint Width;
+        public readonly uint Height;
+        /// <summary>Bytes between the starts of two rows, may include padding.</summary>
+        public readonly uint Stride;
+        public readonly PixelFormat Format;
+        /// <summary>Presentation timestamp in microseconds, -1 when unknown.</summary>
+        public readonly long PtsUs;
+        /// <summary>Capture time in microseconds since the Unix epoch.</summary>
+        public readonly long CapturedAtUs;
+        /// <summary>
+        /// Counts the frames the player delivered, gaps are frames skipped for this
+        /// handler. Frames dropped before delivery are not counted.
+        /// </summary>
+        public readonly ulong FrameNumber;
+
+        /// <summary>The pixel data, starting at the first row.</summary>
+        public unsafe ReadOnlySpan<byte> Pixels => new ReadOnlySpan<byte>((void*)Data, checked((int)Length));
+    }
+
+    /// <summary>
+    /// Receives frames on a thread owned by the native player, one at a time.
+    /// </summary>
+    public delegate void FrameHandler(in RtspFrame frame);
+
+    /// <summary>
+    /// A decoded frame copied into managed memory. Rows are tightly packed.
+    /// </summary>
+    public sealed class Snapshot
+    {
+        public byte[] Data { get; }
+        public int Width { get; }
+        public int Height { get; }
+        public int Stride { get; }
+        public PixelFormat Format { get; }
+
+        internal Snapshot(byte[] data, int width, int height, int stride, PixelFormat format)
+        {
+            Data = data;
+            Width = width;
+            Height = height;
+            Stride = stride;
+            Format = format;
+        }
+    }
+
+    /// <summary>
+    /// A player event. Only the properties listed for its <see cref="Kind"/>
+    /// in <c>rtsp_player.h</c> are meaningful.
+    /// </summary>
+    public sealed class RtspEventArgs : EventArgs
+    {
+        public EventKind Kind { get; }
+        public PlayerState State { get; }
+        public PlayerState OldState { get; }
+        public int BufferingPercent { get; }
+        public ErrorCode ErrorCode { get; }
+        public uint Attempt { get; }
+        public TimeSpan Duration { get; }
+        public uint Width { get; }
+        public uint Height { get; }
+        public double Framerate { get; }
+        public string? Message { get; }
+
+        internal RtspEventArgs(in NativeMethods.Event evt)
+        {
+            Kind = (EventKind)evt.Kind;
+            State = (PlayerState)evt.State;
+            OldState = (PlayerState)evt.OldState;
+            BufferingPercent = evt.BufferingPercent;
+            ErrorCode = (ErrorCode)evt.ErrorCode;
+            Attempt = evt.Attempt;
+            Duration = TimeSpan.FromMilliseconds(evt.DurationMs);
+            Width = evt.Width;
+            Height = evt.Height;
+            Framerate = evt.Framerate;
+            // The native string is only valid during the callback
+            Message = evt.Message == IntPtr.Zero ? null : Marshal.PtrToStringUTF8(evt.Message);
+        }
+    }
+}
@@ -1,72 +1,88 @@
-use gstreamer as gst;
-use gstreamer::prelude::*;
-use gstreamer_video::prelude::VideoOverlayExtManual;
-use gstreamer_video as gst_video;
-use player::RtspPlayer;
+use player::{GridOptions, GridPlayer, RtspPlayer, RtspPlayerConfig};
 use std::env;
 use std::error::Error;
-use std::os::raw::c_void;
-use std::sync::{Arc, Mutex};
-use std::sync::mpsc::{channel, Sender, Receiver};
-use std::time::Duration;
+#[cfg(windows)]
+use player::{Win32GridWindow, Win32Gui};
+#[cfg(windows)]
 use windows::{
-    core::*,
     Win32::Foundation::*,
-    Win32::UI::Controls::*,
     Win32::UI::WindowsAndMessaging::*,
-    Win32::Graphics::Gdi::*,
-    Win32::System::LibraryLoader::GetModuleHandleA,
 };
 
 
+#[cfg(windows)]
 extern "system" fn window_proc(hwnd: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     if message == WM_CREATE {
-        // Store the RtspPlayer instance pointer in the window's user data
+        // Store the Win32Gui instance pointer in the window's user data
         let create_struct = unsafe { &*(lparam.0 as *const CREATESTRUCTA) };
-        let player_ptr_ptr = create_struct.lpCreateParams as *const *const RtspPlayer;
-        let player_ptr = unsafe { *player_ptr_ptr };
-        
+        let gui_ptr_ptr = create_struct.lpCreateParams as *const *const Win32Gui;
+        let gui_ptr = unsafe { *gui_ptr_ptr };
+
         unsafe {
-            SetWindowLongPtrA(hwnd, GWLP_USERDATA, player_ptr as isize);
+            SetWindowLongPtrA(hwnd, GWLP_USERDATA, gui_ptr as isize);
         }
-        
+
         return LRESULT(0);
     }
-    
-    // Get the RtspPlayer instance from the window's user data
-    let player_ptr = unsafe { GetWindowLongPtrA(hwnd, GWLP_USERDATA) } as *const RtspPlayer;
-    
-    if !player_ptr.is_null() {
-        let player = unsafe { &*player_ptr };
-        return player.handle_window_message(hwnd, message, wparam, lparam);
+
+    // Get the Win32Gui instance from the window's user data
+    let gui_ptr = unsafe { GetWindowLongPtrA(hwnd, GWLP_USERDATA) } as *const Win32Gui;
+
+    if !gui_ptr.is_null() {
+        let gui = unsafe { &*gui_ptr };
+        return gui.handle_window_message(hwnd, message, wparam, lparam);
     }
-    
+
     unsafe { DefWindowProcA(hwnd, message, wparam, lparam) }
 }
 
 fn main() -> std::result::Result<(), Box<dyn Error>> {
     // Get the RTSP URL from command line or use a default
     let args: Vec<String> = env::args().collect();
+    if args.len() > 2 {
+        // Several URLs: show them all in one grid window
+        let configs = args[1..]
+            .iter()
+            .map(|url| stream_config(url))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        println!("Initializing grid of {} streams", configs.len());
+        return run_grid(GridPlayer::new(configs, GridOptions::default())?);
+    }
+
     let rtsp_url = if args.len() > 1 {
         args[1].clone()
     } else {
         String::from("rtsp://127.0.0.1:8554/live.sdp") // Default URL
     };
-    
-    println!("Initializing RTSP player for: {}", rtsp_url);
-    
+
     // Create the RTSP player
-    let player = RtspPlayer::new(&rtsp_url)?;
-    
+    let config = stream_config(&rtsp_url)?;
+    println!("Initializing RTSP player for: {}", config.display_name());
+    let player = RtspPlayer::with_config(config)?;
+
+    run(player)
+}
+
+/// Credentials come from `RTSP_USERNAME` and `RTSP_PASSWORD` if set, so they
+/// need not be part of the URL on the command line.
+fn stream_config(url: &str) -> std::result::Result<RtspPlayerConfig, player::PlayerError> {
+    let mut builder = RtspPlayerConfig::builder(url);
+    if let Ok(username) = env::var("RTSP_USERNAME") {
+        let password = env::var("RTSP_PASSWORD").unwrap_or_default();
+        builder = builder.credentials(&username, &password);
+    }
+    builder.build()
+}
+
+#[cfg(windows)]
+fn run(player: RtspPlayer) -> std::result::Result<(), Box<dyn Error>> {
     // Set up GUI
-    player.create_gui(Some(window_proc))?;
-    
-    // Set up message handling
-    player.setup_message_handling()?;
-    
+    let gui = Win32Gui::new(player);
+    gui.create_gui(Some(window_proc))?;
+
     // Start playback
-    player.play()?;
-    
+    gui.player().play()?;
+
     // Windows message loop
     unsafe {
         let mut msg = MSG::default();
@@ -78,26 +94,59 @@ fn main() -> std::result::Result<(), Box<dyn Error>> {
             DispatchMessageA(&msg);
         }
     }
-    
+
     // Clean up
-    player.stop()?;
-    
+    gui.player().stop()?;
+
     Ok(())
 }
 
-// Helper function to get LOWORD
-fn LOWORD(dword: u32) -> u16 {
-    (dword & 0xFFFF) as u16
+#[cfg(not(windows))]
+fn run(player: RtspPlayer) -> std::result::Result<(), Box<dyn Error>> {
+    // No GUI here, the video sink opens its own window. Play until Ctrl+C.
+    let (quit_sender, quit_receiver) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = quit_sender.send(());
+    })?;
+
+    player.play()?;
+    let _ = quit_receiver.recv();
+
+    // Clean up
+    player.stop()?;
+
+    Ok(())
 }
 
-// Helper function to get HIWORD
-fn HIWORD(dword: u32) -> u16 {
-    ((dword >> 16) & 0xFFFF) as u16
+#[cfg(windows)]
+fn run_grid(grid: GridPlayer) -> std::result::Result<(), Box<dyn Error>> {
+    let window = Win32GridWindow::new(grid);
+    window.create_window("RTSP Player Grid")?;
+    window.grid().play()?;
+
+    unsafe {
+        let mut msg = MSG::default();
+        while GetMessageA(&mut msg, None, 0, 0).into() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageA(&msg);
+        }
+    }
+
+    window.grid().stop()?;
+    Ok(())
 }
 
-// Helper function for MAKELONG
-fn MAKELONG(low: u16, high: u16) -> u32 {
-    ((high as u32) << 16) | (low as u32)
+#[cfg(not(windows))]
+fn run_grid(grid: GridPlayer) -> std::result::Result<(), Box<dyn Error>> {
+    let (quit_sender, quit_receiver) = std::sync::mpsc::channel();
+    ctrlc::set_handler(move || {
+        let _ = quit_sender.send(());
+    })?;
+
+    grid.play()?;
+    let _ = quit_receiver.recv();
+    grid.stop()?;
+    Ok(())
 }
 
 // For testing
@@ -108,7 +157,8 @@ mod tests {
     #[test]
     fn test_create_player() {
         let test_url = "rtsp://127.0.0.1:8554/test.sdp";
-        let player = RtspPlayer::new(test_url);
+        let config = RtspPlayerConfig::builder(test_url).build().unwrap();
+        let player = RtspPlayer::with_config(config);
         assert!(player.is_ok(), "Should be able to create a player instance");
     }
 }